@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use cands_interface::{RxData, SIDConfig, XIDConfig};
+use cands_transport::cyphal::CyphalTxPacket;
+
+use super::{encode_fifo_element, CANBackend};
+use crate::MTU_CAN_FD;
+
+/// A CAN frame handed to `MockBackend::transmit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxFrame {
+    pub xid: u32,
+    pub payload: Vec<u8>,
+}
+
+impl TxFrame {
+    /// Encode the frame as a device FIFO element, as if it had been received.
+    pub fn to_fifo_element(&self) -> std::io::Result<Vec<u8>> {
+        encode_fifo_element(self.xid, &self.payload)
+    }
+}
+
+type Responder = Box<dyn FnMut(&TxFrame) -> Vec<Vec<u8>> + Send>;
+
+/// In-memory backend for hardware-free testing.
+///
+/// Every transmitted frame is recorded in `transmitted`.
+/// Received data comes from a queue of FIFO buffers, filled either directly by `push_rx`
+/// or by a responder callback invoked on each transmitted frame.
+/// Each call to `receive` pops one buffer.
+#[derive(Default)]
+pub struct MockBackend {
+    pub transmitted: Vec<TxFrame>,
+    pub setup_count: usize,
+    /// Reported by `transmit_timestamp`; set on every transmit.
+    pub last_transmit: Option<std::time::Instant>,
+    /// Returned by the next `receive` instead of data.
+    pub receive_error: Option<std::io::ErrorKind>,
+    rx_queue: VecDeque<Vec<u8>>,
+    responder: Option<Responder>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reply to every transmitted frame with the FIFO buffers returned by `responder`.
+    pub fn set_responder<F>(&mut self, responder: F)
+    where
+        F: FnMut(&TxFrame) -> Vec<Vec<u8>> + Send + 'static
+    {
+        self.responder = Some(Box::new(responder));
+    }
+
+    pub fn clear_responder(&mut self) {
+        self.responder = None;
+    }
+
+    /// Queue a raw FIFO buffer to be returned by a later `receive`.
+    pub fn push_rx(&mut self, buffer: Vec<u8>) {
+        self.rx_queue.push_back(buffer);
+    }
+
+    /// Queue Cyphal packets as a single FIFO buffer.
+    pub fn push_packets(&mut self, packets: &[CyphalTxPacket<MTU_CAN_FD>]) {
+        self.push_rx(packets_to_fifo(packets));
+    }
+
+    pub fn pending_rx(&self) -> usize {
+        self.rx_queue.len()
+    }
+
+    pub fn take_transmitted(&mut self) -> Vec<TxFrame> {
+        std::mem::take(&mut self.transmitted)
+    }
+}
+
+impl CANBackend for MockBackend {
+    fn setup(&mut self, _sidf: &[SIDConfig], _xidf: &[XIDConfig]) -> Result<(), crate::Error> {
+        self.setup_count += 1;
+        Ok(())
+    }
+
+    fn transmit(&mut self, xid: u32, payload: &[u8], size: usize) -> std::io::Result<()> {
+        let frame: TxFrame = TxFrame { xid, payload: payload[..size].to_vec() };
+
+        if let Some(responder) = self.responder.as_mut() {
+            for buffer in responder(&frame) {
+                self.rx_queue.push_back(buffer);
+            }
+        }
+
+        self.transmitted.push(frame);
+        self.last_transmit = Some(std::time::Instant::now());
+        Ok(())
+    }
+
+    fn receive(&mut self) -> std::io::Result<Option<RxData>> {
+        if let Some(kind) = self.receive_error.take() {
+            return Err(kind.into());
+        }
+        match self.rx_queue.pop_front() {
+            Some(buffer) => {
+                let mut rx_data: RxData = RxData::new();
+                rx_data.fifo1 = buffer;
+                Ok(Some(rx_data))
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn transmit_timestamp(&mut self) -> Option<std::time::Instant> {
+        self.last_transmit
+    }
+}
+
+/// Concatenate Cyphal packets into one device FIFO buffer.
+pub fn packets_to_fifo(packets: &[CyphalTxPacket<MTU_CAN_FD>]) -> Vec<u8> {
+    packets
+        .iter()
+        // A packet never holds more than a CAN FD frame, so encoding cannot fail.
+        .flat_map(|packet| encode_fifo_element(packet.xid, &packet.payload[..packet.payload_size]).unwrap_or_default())
+        .collect()
+}
@@ -0,0 +1,82 @@
+use cands_interface::{RxData, SIDConfig, XIDConfig};
+use cands_transport::cyphal::{CAN_DLC_TO_DLEN, CAN_DLEN_TO_DLC};
+
+mod mock;
+pub use mock::{packets_to_fifo, MockBackend, TxFrame};
+
+mod sim;
+pub use sim::{SimBackend, SimulatedDrive, SIM_ERROR_HISTORY_KEY, SIM_FAULT_RESET_KEY, SIM_RESULT_LENGTH_MISMATCH, SIM_RESULT_OK, SIM_RESULT_TYPE_MISMATCH, SIM_RESULT_UNKNOWN_KEY};
+
+#[cfg(feature="socketcan")]
+mod socketcan;
+#[cfg(feature="socketcan")]
+pub use socketcan::SocketCANBackend;
+
+#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
+use cands_interface::TCAN455xTranceiver;
+
+/// Transport underneath `CANInterface`.
+///
+/// `receive` hands back raw FIFO contents in the TCAN455x layout
+/// (4-byte XID, 4-byte header with DLC in bits 16..20, 64 data bytes per element),
+/// which is what `CyphalMiddleware::try_read` decodes.
+pub trait CANBackend {
+    fn setup(&mut self, sidf: &[SIDConfig], xidf: &[XIDConfig]) -> Result<(), crate::Error>;
+    fn transmit(&mut self, xid: u32, payload: &[u8], size: usize) -> std::io::Result<()>;
+    fn receive(&mut self) -> std::io::Result<Option<RxData>>;
+
+    /// When the last frame passed to `transmit` went out on the bus, for backends whose hardware reports it.
+    /// Callers fall back to a software timestamp taken right after `transmit` returns.
+    fn transmit_timestamp(&mut self) -> Option<std::time::Instant> {
+        None
+    }
+}
+
+#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
+impl CANBackend for TCAN455xTranceiver {
+    fn setup(&mut self, sidf: &[SIDConfig], xidf: &[XIDConfig]) -> Result<(), crate::Error> {
+        TCAN455xTranceiver::setup(self, sidf, xidf).map_err(std::io::Error::other)?;
+        Ok(())
+    }
+
+    fn transmit(&mut self, xid: u32, payload: &[u8], size: usize) -> std::io::Result<()> {
+        TCAN455xTranceiver::transmit(self, xid, payload, size)
+    }
+
+    fn receive(&mut self) -> std::io::Result<Option<RxData>> {
+        TCAN455xTranceiver::receive(self)
+    }
+}
+
+/// Size of one element in the device RX FIFO: XID, header and a full CAN FD payload.
+pub const FIFO_ELEMENT_SIZE: usize = 8 + 64;
+
+/// Encode a CAN FD frame in the TCAN455x RX FIFO element layout.
+///
+/// `payload` is padded up to the next valid CAN FD length,
+/// keeping its last byte (the Cyphal tail byte) at the end of the padded frame.
+/// Fails with `InvalidInput` if `payload` is longer than a CAN FD frame (64 bytes).
+pub fn encode_fifo_element(xid: u32, payload: &[u8]) -> std::io::Result<Vec<u8>> {
+    const FDF: u32 = 1;
+    const BRS: u32 = 1;
+
+    let dlc: u8 = match CAN_DLEN_TO_DLC.get(payload.len()) {
+        Some(dlc) => *dlc,
+        None => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("payload of {} bytes exceeds a CAN FD frame", payload.len())))
+    };
+    let dlen: usize = CAN_DLC_TO_DLEN[dlc as usize] as usize;
+    let header: u32 = (FDF << 21) | (BRS << 20) | ((dlc as u32) << 16);
+
+    let mut element: Vec<u8> = Vec::with_capacity(FIFO_ELEMENT_SIZE);
+    element.extend(xid.to_le_bytes());
+    element.extend(header.to_le_bytes());
+    element.extend(payload);
+    element.resize(FIFO_ELEMENT_SIZE, 0);
+
+    if dlen > payload.len() && !payload.is_empty() {
+        element[8 + dlen - 1] = payload[payload.len() - 1];
+        element[8 + payload.len() - 1] = 0;
+    }
+
+    Ok(element)
+}
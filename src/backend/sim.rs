@@ -0,0 +1,441 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use cands_interface::{RxData, SIDConfig, XIDConfig};
+use cands_presentation::cyphal::digitalservo::{
+    dictionary::{Dict, DigitalServoPrimitiveData, IntoDigitalServoDataType},
+    string::Str,
+};
+use cands_transport::cyphal::{
+    CyphalMiddleware, CyphalRxFrame, CyphalRxPacketType, CyphalTransferKind, CyphalTxPacket, CRC_SIZE_BYTES,
+};
+
+use super::{packets_to_fifo, CANBackend, TxFrame};
+
+const MTU_CAN_FD: usize = 64;
+
+const SET_VALUE_SERVICE_ID: u16 = 0x81;
+const GET_VALUE_SERVICE_ID: u16 = 0x82;
+const VALUE_PORT_ID: u16 = 0x80;
+const RESULT_PORT_ID: u16 = 0x87;
+const BROADCAST_SUBJECT_ID: u16 = 0x488;
+const ERROR_SUBJECT_ID: u16 = 0x17C0;
+const ERROR_HISTORY_LEN: usize = 16;
+
+/// Result codes replied by `SimulatedDrive` on port 0x87. Only 0 is shared with real drives; the others are the simulator's own.
+pub const SIM_RESULT_OK: u8 = 0;
+pub const SIM_RESULT_UNKNOWN_KEY: u8 = 1;
+pub const SIM_RESULT_TYPE_MISMATCH: u8 = 2;
+pub const SIM_RESULT_LENGTH_MISMATCH: u8 = 3;
+
+/// Keys `SimulatedDrive` uses for its fault reset and error history; real firmware may use others.
+pub const SIM_FAULT_RESET_KEY: &str = "faultreset";
+pub const SIM_ERROR_HISTORY_KEY: &str = "errhist";
+
+/// Software DigitalServo drive speaking the v2 protocol.
+///
+/// Set-value requests (0x81) and broadcasts (0x488) write into `values` and,
+/// for requests, are answered with a result code on 0x87. A write must match the type and number of elements
+/// already held by the key.
+/// Get-value requests (0x82) are answered with a `Dict` on 0x80.
+/// Faults raised with `raise` switch `drive` off, are kept in `SIM_ERROR_HISTORY_KEY` and stay latched until
+/// `SIM_FAULT_RESET_KEY` is written.
+pub struct SimulatedDrive {
+    pub node_id: u8,
+    pub values: HashMap<String, Vec<DigitalServoPrimitiveData>>,
+    /// Reply this result code to every set-value request instead of applying it.
+    pub forced_result: Option<u8>,
+    /// Latched fault code, if any.
+    pub fault: Option<u8>,
+    /// Publish the latched fault again instead of clearing it on a fault reset.
+    pub fault_persists: bool,
+    /// How long after the reset a persisting fault is published again.
+    pub fault_repeat_delay: Duration,
+    middleware: CyphalMiddleware<MTU_CAN_FD>,
+    sessions: HashMap<(u8, u16, u8), CyphalRxFrame>,
+    scheduled: Vec<(Instant, Vec<CyphalTxPacket<MTU_CAN_FD>>)>,
+}
+
+impl SimulatedDrive {
+    pub fn new(node_id: u8) -> Self {
+        let mut values: HashMap<String, Vec<DigitalServoPrimitiveData>> = HashMap::new();
+        values.insert("drive".into(), vec![false.into()]);
+        values.insert("cmdval".into(), vec![0.0.into()]);
+        values.insert("cmdarray".into(), vec![0.0.into(); 16]);
+        values.insert(SIM_FAULT_RESET_KEY.into(), vec![false.into()]);
+        values.insert(SIM_ERROR_HISTORY_KEY.into(), vec![0u8.into(); ERROR_HISTORY_LEN]);
+
+        Self {
+            node_id,
+            values,
+            forced_result: None,
+            fault: None,
+            fault_persists: false,
+            fault_repeat_delay: Duration::ZERO,
+            middleware: CyphalMiddleware::<MTU_CAN_FD>::new(node_id),
+            sessions: HashMap::new(),
+            scheduled: vec![],
+        }
+    }
+
+    pub fn with_value<T: Into<DigitalServoPrimitiveData> + Clone>(mut self, key: &str, value: &[T]) -> Self {
+        self.values.insert(key.into(), value.iter().cloned().map(Into::into).collect());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&[DigitalServoPrimitiveData]> {
+        self.values.get(key).map(|value| value.as_slice())
+    }
+
+    /// Feed one CAN frame from the bus and return the packets the drive sends in reply.
+    pub fn handle(&mut self, frame: &TxFrame) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        let packets = match frame.to_fifo_element().map(|element| self.middleware.try_read(&element)) {
+            Ok(Ok(packets)) => packets,
+            _ => return vec![]
+        };
+
+        let mut replies: Vec<CyphalTxPacket<MTU_CAN_FD>> = vec![];
+
+        for packet in packets {
+            let addressed: bool = match packet.props.transfer_kind {
+                CyphalTransferKind::Request => packet.props.destination_node_id == self.node_id,
+                CyphalTransferKind::Message => packet.props.port_id == BROADCAST_SUBJECT_ID,
+                CyphalTransferKind::Response => false,
+            };
+            if !addressed {
+                continue;
+            }
+
+            let session: (u8, u16, u8) = (packet.props.source_node_id, packet.props.port_id, packet.props.transfer_id);
+            let payload: &[u8] = &packet.payload[..packet.payload_size];
+
+            let transfer: Option<Vec<u8>> = match packet.status.frame_type {
+                CyphalRxPacketType::SignleFrame => Some(payload.to_vec()),
+                CyphalRxPacketType::MultiFrameStart => {
+                    self.sessions.insert(session, CyphalRxFrame {
+                        xid: packet.xid,
+                        payload: payload.to_vec(),
+                        payload_size: packet.payload_size,
+                        props: packet.props,
+                    });
+                    None
+                },
+                CyphalRxPacketType::MultiFrameInProcess => {
+                    if let Some(frame) = self.sessions.get_mut(&session) {
+                        frame.payload.extend(payload);
+                        frame.payload_size += packet.payload_size;
+                    }
+                    None
+                },
+                CyphalRxPacketType::MultiFrameEnd => {
+                    match self.sessions.remove(&session) {
+                        Some(mut frame) => {
+                            frame.payload.extend(payload);
+                            frame.payload_size += packet.payload_size;
+
+                            // The transfer CRC may be split over the last two frames.
+                            let crc_size: usize = CRC_SIZE_BYTES as usize;
+                            match frame.payload_size.checked_sub(crc_size) {
+                                Some(data_size) => {
+                                    let crc_expected: Vec<u8> = frame.payload.split_off(data_size);
+                                    frame.payload_size = data_size;
+                                    match frame.calculate_crc() {
+                                        Ok(crc) if crc[..] == crc_expected[..] => Some(frame.payload),
+                                        _ => None
+                                    }
+                                },
+                                None => None
+                            }
+                        },
+                        None => None
+                    }
+                }
+            };
+
+            if let Some(transfer) = transfer {
+                replies.extend(self.process(packet.props.transfer_kind, packet.props.port_id, packet.props.source_node_id, packet.props.transfer_id, &transfer));
+            }
+        }
+
+        replies
+    }
+
+    /// Build the packets of an error report on 0x17C0.
+    pub fn error_packets(&mut self, code: u8) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        self.middleware.create_message_data(ERROR_SUBJECT_ID, &[code], 1).unwrap_or_default()
+    }
+
+    /// Latch a fault: switch `drive` off, shift `code` into the error history and build its error report.
+    pub fn raise(&mut self, code: u8) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        self.fault = Some(code);
+        self.values.insert("drive".into(), vec![false.into()]);
+        let history: &mut Vec<DigitalServoPrimitiveData> = self.values.entry(SIM_ERROR_HISTORY_KEY.into()).or_default();
+        history.push(code.into());
+        history.remove(0);
+        self.error_packets(code)
+    }
+
+    /// Take the packets the drive sends on its own by `now`.
+    pub fn due_packets(&mut self, now: Instant) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        let (due, scheduled) = std::mem::take(&mut self.scheduled).into_iter().partition(|(at, _)| *at <= now);
+        self.scheduled = scheduled;
+        due.into_iter().flat_map(|(_, packets)| packets).collect()
+    }
+
+    /// Handle a write to the fault reset key, reporting the fault again after `fault_repeat_delay` if it persists.
+    fn reset_fault(&mut self) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        match (self.fault, self.fault_persists) {
+            (Some(code), true) => {
+                let packets: Vec<CyphalTxPacket<MTU_CAN_FD>> = self.error_packets(code);
+                match self.fault_repeat_delay.is_zero() {
+                    true => packets,
+                    false => {
+                        self.scheduled.push((Instant::now() + self.fault_repeat_delay, packets));
+                        vec![]
+                    }
+                }
+            },
+            _ => {
+                self.fault = None;
+                vec![]
+            }
+        }
+    }
+
+    fn process(&mut self, kind: CyphalTransferKind, port_id: u16, source_node_id: u8, transfer_id: u8, payload: &[u8]) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        match (kind, port_id) {
+            (CyphalTransferKind::Message, BROADCAST_SUBJECT_ID) => {
+                if let Ok(dict) = Dict::deserialize(payload) {
+                    self.apply(dict);
+                }
+                vec![]
+            },
+            (CyphalTransferKind::Request, SET_VALUE_SERVICE_ID) => {
+                let (result, reset): (u8, bool) = match (self.forced_result, Dict::deserialize(payload)) {
+                    (Some(result), _) => (result, false),
+                    (None, Ok(dict)) => {
+                        let reset: bool = (dict.key == SIM_FAULT_RESET_KEY) && (dict.value.first() == Some(&true.into()));
+                        (self.apply(dict), reset)
+                    },
+                    (None, Err(_)) => (SIM_RESULT_TYPE_MISMATCH, false),
+                };
+                let mut replies: Vec<CyphalTxPacket<MTU_CAN_FD>> = match (result == SIM_RESULT_OK) && reset {
+                    true => self.reset_fault(),
+                    false => vec![]
+                };
+                replies.extend(self.reply(source_node_id, RESULT_PORT_ID, transfer_id, &[result]));
+                replies
+            },
+            (CyphalTransferKind::Request, GET_VALUE_SERVICE_ID) => {
+                let key: String = match Str::deserialize(payload) {
+                    Ok(key) => key.value,
+                    Err(_) => return vec![]
+                };
+                match self.values.get(&key) {
+                    Some(value) => {
+                        let payload: Vec<u8> = serialize_values(&key, value);
+                        self.reply(source_node_id, VALUE_PORT_ID, transfer_id, &payload)
+                    },
+                    None => self.reply(source_node_id, RESULT_PORT_ID, transfer_id, &[SIM_RESULT_UNKNOWN_KEY])
+                }
+            },
+            _ => vec![]
+        }
+    }
+
+    fn apply(&mut self, dict: Dict) -> u8 {
+        match self.values.get_mut(&dict.key) {
+            Some(value) => {
+                let same_type: bool = match (value.first(), dict.value.first()) {
+                    (Some(current), Some(new)) => std::mem::discriminant(current) == std::mem::discriminant(new),
+                    _ => true
+                };
+                if !same_type {
+                    return SIM_RESULT_TYPE_MISMATCH;
+                }
+                // Every key holds a fixed number of elements, like on a drive.
+                if value.len() != dict.value.len() {
+                    return SIM_RESULT_LENGTH_MISMATCH;
+                }
+                *value = dict.value;
+                SIM_RESULT_OK
+            },
+            None => SIM_RESULT_UNKNOWN_KEY
+        }
+    }
+
+    fn reply(&mut self, destination: u8, port_id: u16, transfer_id: u8, payload: &[u8]) -> Vec<CyphalTxPacket<MTU_CAN_FD>> {
+        self.middleware.transfer_id = transfer_id;
+        self.middleware.create_response_data(destination, port_id, payload, payload.len()).unwrap_or_default()
+    }
+}
+
+/// Backend connecting `CANInterface` to a set of `SimulatedDrive`s.
+///
+/// Frames can be dropped at random in either direction and replies are delivered after `latency`,
+/// which is enough to exercise the timeout and retry logic without hardware.
+pub struct SimBackend {
+    pub drives: Vec<SimulatedDrive>,
+    pub latency: Duration,
+    /// Probability in [0, 1] that a frame sent by the host is lost.
+    pub request_loss: f64,
+    /// Probability in [0, 1] that a frame sent by a drive is lost.
+    pub reply_loss: f64,
+    pub transmitted: usize,
+    pending: VecDeque<(Instant, Vec<u8>)>,
+    rng: u64,
+}
+
+impl Default for SimBackend {
+    fn default() -> Self {
+        Self {
+            drives: vec![],
+            latency: Duration::ZERO,
+            request_loss: 0.0,
+            reply_loss: 0.0,
+            transmitted: 0,
+            pending: VecDeque::new(),
+            rng: 0x2545_F491_4F6C_DD1D,
+        }
+    }
+}
+
+impl SimBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_drive(mut self, drive: SimulatedDrive) -> Self {
+        self.drives.push(drive);
+        self
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub fn with_packet_loss(mut self, request_loss: f64, reply_loss: f64) -> Self {
+        self.request_loss = request_loss;
+        self.reply_loss = reply_loss;
+        self
+    }
+
+    /// Seed the generator deciding which frames are lost.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = seed.max(1);
+        self
+    }
+
+    pub fn drive(&self, node_id: u8) -> Option<&SimulatedDrive> {
+        self.drives.iter().find(|drive| drive.node_id == node_id)
+    }
+
+    pub fn drive_mut(&mut self, node_id: u8) -> Option<&mut SimulatedDrive> {
+        self.drives.iter_mut().find(|drive| drive.node_id == node_id)
+    }
+
+    /// Make a drive latch a fault and publish its error code on 0x17C0.
+    pub fn raise_error(&mut self, node_id: u8, code: u8) {
+        let packets = match self.drive_mut(node_id) {
+            Some(drive) => drive.raise(code),
+            None => return
+        };
+        self.deliver(packets);
+    }
+
+    fn deliver(&mut self, packets: Vec<CyphalTxPacket<MTU_CAN_FD>>) {
+        let deliver_at: Instant = Instant::now() + self.latency;
+        for packet in packets {
+            if self.lost(self.reply_loss) {
+                continue;
+            }
+            self.pending.push_back((deliver_at, packets_to_fifo(&[packet])));
+        }
+    }
+
+    fn lost(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng as f64 / u64::MAX as f64) < probability
+    }
+}
+
+impl CANBackend for SimBackend {
+    fn setup(&mut self, _sidf: &[SIDConfig], _xidf: &[XIDConfig]) -> Result<(), crate::Error> {
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn transmit(&mut self, xid: u32, payload: &[u8], size: usize) -> std::io::Result<()> {
+        self.transmitted += 1;
+        if self.lost(self.request_loss) {
+            return Ok(());
+        }
+
+        let frame: TxFrame = TxFrame { xid, payload: payload[..size].to_vec() };
+        let replies: Vec<CyphalTxPacket<MTU_CAN_FD>> = self.drives
+            .iter_mut()
+            .flat_map(|drive| drive.handle(&frame))
+            .collect();
+        self.deliver(replies);
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> std::io::Result<Option<RxData>> {
+        let packets: Vec<CyphalTxPacket<MTU_CAN_FD>> = self.drives
+            .iter_mut()
+            .flat_map(|drive| drive.due_packets(Instant::now()))
+            .collect();
+        self.deliver(packets);
+
+        let now: Instant = Instant::now();
+        let mut rx_data: RxData = RxData::new();
+
+        while let Some((deliver_at, _)) = self.pending.front() {
+            if *deliver_at > now {
+                break;
+            }
+            if let Some((_, buffer)) = self.pending.pop_front() {
+                rx_data.fifo1.extend(buffer);
+            }
+        }
+
+        match rx_data.fifo1.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(rx_data))
+        }
+    }
+}
+
+/// Serialize a stored value back into a `Dict` payload.
+fn serialize_values(key: &str, value: &[DigitalServoPrimitiveData]) -> Vec<u8> {
+    fn typed<T>(key: &str, value: &[DigitalServoPrimitiveData]) -> Vec<u8>
+    where
+        T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData> + TryFrom<DigitalServoPrimitiveData>
+    {
+        let value: Vec<T> = value.iter().cloned().filter_map(|x| T::try_from(x).ok()).collect();
+        Dict::serialize(key, &value)
+    }
+
+    match value.first() {
+        Some(DigitalServoPrimitiveData::String(_)) => typed::<String>(key, value),
+        Some(DigitalServoPrimitiveData::Bool(_)) => typed::<bool>(key, value),
+        Some(DigitalServoPrimitiveData::U8(_)) => typed::<u8>(key, value),
+        Some(DigitalServoPrimitiveData::U16(_)) => typed::<u16>(key, value),
+        Some(DigitalServoPrimitiveData::U32(_)) => typed::<u32>(key, value),
+        Some(DigitalServoPrimitiveData::U64(_)) => typed::<u64>(key, value),
+        Some(DigitalServoPrimitiveData::I8(_)) => typed::<i8>(key, value),
+        Some(DigitalServoPrimitiveData::I16(_)) => typed::<i16>(key, value),
+        Some(DigitalServoPrimitiveData::I32(_)) => typed::<i32>(key, value),
+        Some(DigitalServoPrimitiveData::I64(_)) => typed::<i64>(key, value),
+        Some(DigitalServoPrimitiveData::F32(_)) => typed::<f32>(key, value),
+        Some(DigitalServoPrimitiveData::F64(_)) | None => typed::<f64>(key, value),
+    }
+}
@@ -0,0 +1,143 @@
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use cands_interface::{RxData, SIDConfig, XIDConfig};
+
+use super::{encode_fifo_element, CANBackend};
+
+/// Linux SocketCAN backend.
+///
+/// Frames are exchanged as CAN FD frames with bit rate switching over a raw socket,
+/// so the interface must be CAN FD capable (for vcan: `ip link set vcan0 mtu 72`).
+/// Bit timing is configured on the interface itself (e.g. with `ip link`), not by this crate.
+pub struct SocketCANBackend {
+    socket: OwnedFd,
+    interface: String,
+}
+
+impl SocketCANBackend {
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let name: CString = CString::new(interface).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        let ifindex: libc::c_uint = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd: libc::c_int = unsafe { libc::socket(libc::PF_CAN, libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, libc::CAN_RAW) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket: OwnedFd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let enable: libc::c_int = 1;
+        Self::set_option(&socket, libc::CAN_RAW_FD_FRAMES, &enable)?;
+
+        let mut address: libc::sockaddr_can = unsafe { std::mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = ifindex as libc::c_int;
+        let ret: libc::c_int = unsafe {
+            libc::bind(
+                socket.as_raw_fd(),
+                &address as *const libc::sockaddr_can as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { socket, interface: interface.into() })
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    fn set_option<T>(socket: &OwnedFd, name: libc::c_int, value: &T) -> io::Result<()> {
+        let ret: libc::c_int = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_CAN_RAW,
+                name,
+                value as *const T as *const libc::c_void,
+                std::mem::size_of::<T>() as libc::socklen_t,
+            )
+        };
+        match ret < 0 {
+            true => Err(io::Error::last_os_error()),
+            false => Ok(())
+        }
+    }
+}
+
+impl CANBackend for SocketCANBackend {
+    /// The TCAN455x acceptance filters do not apply here;
+    /// the socket is restricted to extended frames, which is all Cyphal uses.
+    fn setup(&mut self, _sidf: &[SIDConfig], _xidf: &[XIDConfig]) -> Result<(), crate::Error> {
+        let filter: libc::can_filter = libc::can_filter {
+            can_id: libc::CAN_EFF_FLAG,
+            can_mask: libc::CAN_EFF_FLAG,
+        };
+        Self::set_option(&self.socket, libc::CAN_RAW_FILTER, &filter)?;
+        Ok(())
+    }
+
+    fn transmit(&mut self, xid: u32, payload: &[u8], size: usize) -> io::Result<()> {
+        let mut frame: libc::canfd_frame = unsafe { std::mem::zeroed() };
+        frame.can_id = (xid & libc::CAN_EFF_MASK) | libc::CAN_EFF_FLAG;
+        frame.len = size as u8;
+        frame.flags = (libc::CANFD_BRS | libc::CANFD_FDF) as u8;
+        frame.data[..size].copy_from_slice(&payload[..size]);
+
+        let ret: isize = unsafe {
+            libc::write(
+                self.socket.as_raw_fd(),
+                &frame as *const libc::canfd_frame as *const libc::c_void,
+                libc::CANFD_MTU,
+            )
+        };
+        match ret < 0 {
+            true => Err(io::Error::last_os_error()),
+            false => Ok(())
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<Option<RxData>> {
+        let mut rx_data: RxData = RxData::new();
+
+        loop {
+            let mut frame: libc::canfd_frame = unsafe { std::mem::zeroed() };
+            let ret: isize = unsafe {
+                libc::read(
+                    self.socket.as_raw_fd(),
+                    &mut frame as *mut libc::canfd_frame as *mut libc::c_void,
+                    libc::CANFD_MTU,
+                )
+            };
+
+            if ret < 0 {
+                let err: io::Error = io::Error::last_os_error();
+                match err.kind() {
+                    io::ErrorKind::WouldBlock => break,
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(err)
+                }
+            }
+
+            // Classic frames (CAN_MTU) share the layout of the leading part of canfd_frame.
+            // Every Cyphal frame carries at least a tail byte.
+            let len: usize = std::cmp::min(frame.len as usize, frame.data.len());
+            if (frame.can_id & libc::CAN_EFF_FLAG) == 0 || len == 0 {
+                continue;
+            }
+            rx_data.fifo1.extend(encode_fifo_element(frame.can_id & libc::CAN_EFF_MASK, &frame.data[..len])?);
+        }
+
+        match rx_data.fifo1.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(rx_data))
+        }
+    }
+}
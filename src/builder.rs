@@ -0,0 +1,209 @@
+use cands_interface::{SIDConfig, XIDConfig};
+use cands_transport::cyphal::{CyphalMiddleware, CYPHAL_NODE_ID_MAX, CYPHAL_TRANSFER_ID_MAX};
+
+#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
+use cands_interface::TCAN455xTranceiver;
+
+use crate::{CANBackend, CANInterface, CollisionPolicy, Error, RxStats, DEFAULT_MAX_INCOMPLETE_TRANSFERS, DEFAULT_TRANSFER_ID_TIMEOUT, MTU_CAN_FD, NODE_ID, SIDF, XIDF};
+
+/// Number of filter elements reserved in the TCAN455x message RAM.
+pub const SIDF_NUM: usize = 2;
+pub const XIDF_NUM: usize = 1;
+
+/// Configures a `CANInterface` before it touches the backend.
+///
+/// ```ignore
+/// let interface = CANInterfaceBuilder::new()
+///     .node_id(100)
+///     .timeout(std::time::Duration::from_millis(20))
+///     .build(backend)?;
+/// ```
+#[derive(Clone)]
+pub struct CANInterfaceBuilder {
+    node_id: u8,
+    sidf: [SIDConfig; SIDF_NUM],
+    xidf: [XIDConfig; XIDF_NUM],
+    initial_transfer_id: Option<u8>,
+    transfer_id_timeout: std::time::Duration,
+    max_incomplete_transfers: usize,
+    timeout: std::time::Duration,
+    retry_count: u32,
+    collision_check: Option<(std::time::Duration, CollisionPolicy)>,
+    #[cfg(feature="drvcan_v2")]
+    fault_keys: crate::digitalservo::v2::FaultKeys,
+    #[cfg(feature="drvcan_v2")]
+    fault_settle_time: std::time::Duration,
+}
+
+impl Default for CANInterfaceBuilder {
+    fn default() -> Self {
+        Self {
+            node_id: NODE_ID,
+            sidf: SIDF,
+            xidf: XIDF,
+            initial_transfer_id: None,
+            transfer_id_timeout: DEFAULT_TRANSFER_ID_TIMEOUT,
+            max_incomplete_transfers: DEFAULT_MAX_INCOMPLETE_TRANSFERS,
+            timeout: crate::DEFAULT_TIMEOUT,
+            retry_count: crate::DEFAULT_RETRY_COUNT,
+            collision_check: None,
+            #[cfg(feature="drvcan_v2")]
+            fault_keys: Default::default(),
+            #[cfg(feature="drvcan_v2")]
+            fault_settle_time: crate::DEFAULT_FAULT_SETTLE_TIME,
+        }
+    }
+}
+
+impl CANInterfaceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_id(mut self, node_id: u8) -> Self {
+        self.node_id = node_id;
+        self
+    }
+
+    /// Standard ID acceptance filters written to the device on `init`.
+    pub fn sid_filters(mut self, sidf: [SIDConfig; SIDF_NUM]) -> Self {
+        self.sidf = sidf;
+        self
+    }
+
+    /// Extended ID acceptance filters written to the device on `init`.
+    pub fn xid_filters(mut self, xidf: [XIDConfig; XIDF_NUM]) -> Self {
+        self.xidf = xidf;
+        self
+    }
+
+    /// Start from a fixed transfer ID instead of one derived from the clock.
+    pub fn initial_transfer_id(mut self, transfer_id: u8) -> Self {
+        self.initial_transfer_id = Some(transfer_id);
+        self
+    }
+
+    /// Drop incomplete multi-frame transfers whose first frame is older than this.
+    pub fn transfer_id_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.transfer_id_timeout = timeout;
+        self
+    }
+
+    /// Upper bound on concurrent reassemblies; the oldest one is dropped to make room.
+    pub fn max_incomplete_transfers(mut self, max: usize) -> Self {
+        self.max_incomplete_transfers = max;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retry_count(mut self, retry_count: u32) -> Self {
+        self.retry_count = retry_count;
+        self
+    }
+
+    /// Listen for `window` after init and apply `policy` if another node uses our node ID.
+    pub fn collision_check(mut self, window: std::time::Duration, policy: CollisionPolicy) -> Self {
+        self.collision_check = Some((window, policy));
+        self
+    }
+
+    /// Keys of the drive firmware for `clear_fault` and `read_error_history`.
+    #[cfg(feature="drvcan_v2")]
+    pub fn fault_keys(mut self, fault_keys: crate::digitalservo::v2::FaultKeys) -> Self {
+        self.fault_keys = fault_keys;
+        self
+    }
+
+    /// How long `drive_enable` and `clear_fault` keep listening for an error before confirming the transition.
+    /// Defaults to 50 ms.
+    #[cfg(feature="drvcan_v2")]
+    pub fn fault_settle_time(mut self, settle_time: std::time::Duration) -> Self {
+        self.fault_settle_time = settle_time;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.node_id > CYPHAL_NODE_ID_MAX {
+            return Err(Error::InvalidConfig(format!("node id {} exceeds {}", self.node_id, CYPHAL_NODE_ID_MAX)));
+        }
+        if let Some(transfer_id) = self.initial_transfer_id {
+            if transfer_id > CYPHAL_TRANSFER_ID_MAX {
+                return Err(Error::InvalidConfig(format!("transfer id {} exceeds {}", transfer_id, CYPHAL_TRANSFER_ID_MAX)));
+            }
+        }
+        for (i, sidf) in self.sidf.iter().enumerate() {
+            if sidf.sft > 0x3 || sidf.sfec > 0x7 || sidf.sidf1 > 0x7FF || sidf.sidf2 > 0x7FF {
+                return Err(Error::InvalidConfig(format!("standard id filter {} is out of range", i)));
+            }
+        }
+        for (i, xidf) in self.xidf.iter().enumerate() {
+            if xidf.eft > 0x3 || xidf.efec > 0x7 || xidf.eidf1 > 0x1FFF_FFFF || xidf.eidf2 > 0x1FFF_FFFF {
+                return Err(Error::InvalidConfig(format!("extended id filter {} is out of range", i)));
+            }
+        }
+        if self.transfer_id_timeout.is_zero() {
+            return Err(Error::InvalidConfig("transfer id timeout must be non-zero".into()));
+        }
+        if self.max_incomplete_transfers == 0 {
+            return Err(Error::InvalidConfig("max incomplete transfers must be non-zero".into()));
+        }
+        if self.timeout.is_zero() {
+            return Err(Error::InvalidConfig("timeout must be non-zero".into()));
+        }
+        if self.retry_count == 0 {
+            return Err(Error::InvalidConfig("retry count must be non-zero".into()));
+        }
+        if self.collision_check.is_some_and(|(window, _)| window.is_zero()) {
+            return Err(Error::InvalidConfig("collision check window must be non-zero".into()));
+        }
+        Ok(())
+    }
+
+    /// Validate the configuration, then build and initialize an interface on `backend`.
+    pub fn build<B: CANBackend>(self, backend: B) -> Result<CANInterface<B>, Error> {
+        self.validate()?;
+
+        let mut interface: CANInterface<B> = CANInterface {
+            middleware: CyphalMiddleware::<MTU_CAN_FD>::new(self.node_id),
+            driver: backend,
+            node_id: self.node_id,
+            sidf: self.sidf,
+            xidf: self.xidf,
+            initial_transfer_id: self.initial_transfer_id,
+            rx_complete_fifo: vec![],
+            rx_incomplete_fifo: vec![],
+            rx_stats: RxStats::default(),
+            transfer_id_timeout: self.transfer_id_timeout,
+            max_incomplete_transfers: self.max_incomplete_transfers,
+            timeout: self.timeout,
+            retry_count: self.retry_count,
+            subject_transfer_ids: Default::default(),
+            #[cfg(feature="drvcan_v2")]
+            drive_states: Default::default(),
+            #[cfg(feature="drvcan_v2")]
+            fault_keys: self.fault_keys,
+            #[cfg(feature="drvcan_v2")]
+            fault_settle_time: self.fault_settle_time,
+        };
+        interface.init()?;
+
+        if let Some((window, policy)) = self.collision_check {
+            interface.claim_node_id(window, policy)?;
+        }
+
+        Ok(interface)
+    }
+
+    /// Validate the configuration, then open the TCAN455x board.
+    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
+    pub fn build_tcan455x(self) -> Result<CANInterface<TCAN455xTranceiver>, Error> {
+        self.validate()?;
+        let driver: TCAN455xTranceiver = TCAN455xTranceiver::new()
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+        self.build(driver)
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use cands_transport::cyphal::{CyphalMiddleware, CYPHAL_NODE_ID_MAX};
+
+use crate::{CANBackend, CANInterface, Error, MTU_CAN_FD, RESPONSE_POLLING_INTERVAL};
+
+/// What to do when another node already transmits with our node ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Fail with `Error::NodeIdCollision`.
+    Fail,
+    /// Take the nearest node ID below ours (then above) that was not heard on the bus.
+    PickFree,
+}
+
+impl<B: CANBackend> CANInterface<B> {
+    /// Change the source node ID, keeping the transfer ID sequence.
+    pub fn set_node_id(&mut self, node_id: u8) {
+        let transfer_id: u8 = self.middleware.transfer_id;
+        self.middleware = CyphalMiddleware::<MTU_CAN_FD>::new(node_id);
+        self.middleware.transfer_id = transfer_id;
+        self.node_id = node_id;
+    }
+
+    /// Receive without transmitting for `window` and return the source node IDs heard.
+    /// Received transfers stay in `rx_complete_fifo`.
+    pub fn listen_for_node_ids(&mut self, window: Duration) -> Result<BTreeSet<u8>, Error> {
+        let deadline: Instant = Instant::now() + window;
+        let mut ret: BTreeSet<u8> = BTreeSet::new();
+
+        loop {
+            self.load_frames()?;
+            ret.extend(self.rx_complete_fifo.iter()
+                .map(|frame| frame.props.source_node_id)
+                .filter(|node_id| *node_id <= CYPHAL_NODE_ID_MAX));
+
+            if Instant::now() >= deadline {
+                return Ok(ret);
+            }
+            std::thread::sleep(RESPONSE_POLLING_INTERVAL);
+        }
+    }
+
+    /// Listen for `window` and make sure no other node uses our node ID before going active.
+    ///
+    /// Only nodes that transmit within the window are seen, so it should span at least one heartbeat period.
+    /// Returns the node ID in use afterwards.
+    pub fn claim_node_id(&mut self, window: Duration, policy: CollisionPolicy) -> Result<u8, Error> {
+        let node_id: u8 = self.node_id;
+        let in_use: BTreeSet<u8> = self.listen_for_node_ids(window)?;
+
+        if !in_use.contains(&node_id) {
+            return Ok(node_id);
+        }
+
+        match policy {
+            CollisionPolicy::Fail => Err(Error::NodeIdCollision { node_id }),
+            CollisionPolicy::PickFree => {
+                let free: Option<u8> = (0..node_id).rev()
+                    .chain(node_id + 1..=CYPHAL_NODE_ID_MAX)
+                    .find(|node_id| !in_use.contains(node_id));
+                match free {
+                    Some(free) => {
+                        self.set_node_id(free);
+                        Ok(free)
+                    },
+                    None => Err(Error::NodeIdCollision { node_id })
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// Errors returned by `CANInterface`.
+#[derive(Debug)]
+pub enum Error {
+    /// The CAN backend failed to set up, transmit or receive.
+    Driver(std::io::Error),
+    /// A Cyphal frame or a DigitalServo payload could not be encoded or decoded.
+    Serialization(String),
+    /// No reply arrived within `timeout` on any of the `attempts`.
+    Timeout { channel: u8, key: String, attempts: u32 },
+    /// The drive answered a request with a non-zero result code.
+    DriveResult { channel: u8, key: String, code: crate::digitalservo::ResultCode },
+    /// The axis is not in a state that allows the action.
+    InvalidDriveState { channel: u8, state: crate::digitalservo::DriveState, action: String },
+    /// The drive reported an error while it was being enabled or commanded.
+    DriveFault { channel: u8, code: crate::digitalservo::ErrorCode },
+    /// A typed parameter value was rejected before transmission.
+    InvalidValue { key: String, reason: String },
+    /// A received value could not be converted into the requested type.
+    TypeConversion { key: String },
+    /// A `CANInterfaceBuilder` setting was rejected before the backend was touched.
+    InvalidConfig(String),
+    /// The background reader of a `CANRuntime` has stopped.
+    Closed,
+    /// A request to `channel` with the same transfer ID is still waiting for its response.
+    TransferIdInUse { channel: u8, transfer_id: u8 },
+    /// Another node transmitted with our node ID during the startup listen window.
+    NodeIdCollision { node_id: u8 },
+    /// A firmware update was refused or the node came back unhealthy.
+    FirmwareUpdate { channel: u8, reason: String },
+    /// A file kept by the crate, such as the PnP allocation table, could not be read or written.
+    Storage(std::io::Error),
+}
+
+impl Error {
+    pub(crate) fn serialization(err: Box<dyn std::error::Error>) -> Self {
+        Self::Serialization(err.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Driver(err) => write!(f, "driver error: {}", err),
+            Self::Serialization(msg) => write!(f, "serialization error: {}", msg),
+            Self::Timeout { channel, key, attempts } => write!(f, "no reply from node {} for \"{}\" after {} attempts", channel, key, attempts),
+            Self::DriveResult { channel, key, code } => write!(f, "node {} rejected \"{}\": {}", channel, key, code),
+            Self::InvalidDriveState { channel, state, action } => write!(f, "node {} is {}, cannot {}", channel, state, action),
+            Self::DriveFault { channel, code } => write!(f, "node {} reports {}", channel, code),
+            Self::InvalidValue { key, reason } => write!(f, "invalid value for \"{}\": {}", key, reason),
+            Self::TypeConversion { key } => write!(f, "value of \"{}\" has an unexpected type", key),
+            Self::InvalidConfig(msg) => write!(f, "invalid configuration: {}", msg),
+            Self::Closed => write!(f, "background reader stopped"),
+            Self::TransferIdInUse { channel, transfer_id } => write!(f, "a request to node {} with transfer id {} is still pending", channel, transfer_id),
+            Self::NodeIdCollision { node_id } => write!(f, "node id {} is already in use on the bus", node_id),
+            Self::FirmwareUpdate { channel, reason } => write!(f, "firmware update of node {} failed: {}", channel, reason),
+            Self::Storage(err) => write!(f, "storage error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Driver(err) => Some(err),
+            Self::Storage(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Driver(err)
+    }
+}
@@ -1,240 +1,291 @@
 pub use cands_interface::{TCAN455xTranceiver, RxData, SIDConfig, XIDConfig};
 pub use cands_transport::cyphal::{CyphalMiddleware, CyphalRxFrame, CyphalRxPacketType, CRC_SIZE_BYTES};
+use cands_transport::cyphal::{CyphalTransferKind, CYPHAL_TRANSFER_ID_MAX};
 pub use cands_presentation::cyphal as serde;
 
+pub mod backend;
+pub use backend::CANBackend;
+
+mod error;
+pub use error::Error;
+
+mod builder;
+pub use builder::{CANInterfaceBuilder, SIDF_NUM, XIDF_NUM};
+
+mod runtime;
+pub use runtime::{CANRuntime, Subscription};
+
+mod reassembly;
+pub use reassembly::{IncompleteTransfer, RxStats, TimestampedRxData, TimestampedRxFrame};
+
+mod collision;
+pub use collision::CollisionPolicy;
+
 mod special_instructions;
-pub use special_instructions::digitalservo;
+pub use special_instructions::{digitalservo, uavcan};
 
 const MTU_CAN_FD: usize = 64;
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const NODE_ID: u8 = 127;
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const SIDF1: SIDConfig = SIDConfig { sft: 3, sfec: 0, sidf1: 0x123, sidf2: 0x456 };
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const SIDF2: SIDConfig = SIDConfig { sft: 3, sfec: 5, sidf1: 0x123, sidf2: 0x456 };
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const XIDF1: XIDConfig = XIDConfig { eft: 0, efec: 0, eidf1: 0x55555, eidf2: 0x77777 };
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const SIDF: [SIDConfig; 2] = [SIDF1, SIDF2];
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 const XIDF: [XIDConfig; 1] = [XIDF1];
 
 #[cfg(any(feature="raspberrypi", feature="raspberrypi_cm"))]
 use cands_interface::GPIO_INPUT_PIN_NUM;
 
-#[cfg(all(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"), feature="drvcan_v2"))]
+const DEFAULT_TRANSFER_ID_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+const DEFAULT_MAX_INCOMPLETE_TRANSFERS: usize = 64;
+
+const RESPONSE_POLLING_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1);
+
 const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(50);
-#[cfg(all(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"), feature="drvcan_v2"))]
 const DEFAULT_RETRY_COUNT: u32 = 20;
 
+#[cfg(feature="drvcan_v2")]
+const DEFAULT_FAULT_SETTLE_TIME: std::time::Duration = std::time::Duration::from_millis(50);
+
 
-pub struct CANInterface {
+pub struct CANInterface<B = TCAN455xTranceiver> {
     pub middleware: CyphalMiddleware<MTU_CAN_FD>,
-    pub driver: TCAN455xTranceiver,
-    pub rx_complete_fifo: Vec<CyphalRxFrame>,
-    pub rx_incomplete_fifo: Vec<CyphalRxFrame>,
-    #[cfg(feature="drvcan_v2")]
+    pub driver: B,
+    pub node_id: u8,
+    pub sidf: [SIDConfig; SIDF_NUM],
+    pub xidf: [XIDConfig; XIDF_NUM],
+    pub initial_transfer_id: Option<u8>,
+    pub rx_complete_fifo: Vec<TimestampedRxFrame>,
+    pub rx_incomplete_fifo: Vec<IncompleteTransfer>,
+    pub rx_stats: RxStats,
+    pub transfer_id_timeout: std::time::Duration,
+    /// Upper bound on concurrent reassemblies; 0 behaves like 1.
+    pub max_incomplete_transfers: usize,
     pub timeout: std::time::Duration,
-    #[cfg(feature="drvcan_v2")]
     pub retry_count: u32,
+    /// Next transfer ID of each subject; a subscriber expects consecutive IDs on one subject.
+    pub(crate) subject_transfer_ids: std::collections::BTreeMap<u16, u8>,
+    #[cfg(feature="drvcan_v2")]
+    pub(crate) drive_states: std::collections::BTreeMap<u8, digitalservo::DriveState>,
+    #[cfg(feature="drvcan_v2")]
+    pub fault_keys: digitalservo::v2::FaultKeys,
+    /// How long `drive_enable` and `clear_fault` keep listening for an error before confirming the transition.
+    #[cfg(feature="drvcan_v2")]
+    pub fault_settle_time: std::time::Duration,
 }
 
 
-impl CANInterface {
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let middleware: CyphalMiddleware<MTU_CAN_FD> = CyphalMiddleware::<MTU_CAN_FD>::new(NODE_ID);
-        let driver: TCAN455xTranceiver = TCAN455xTranceiver::new()?;
-
-        let mut interface: Self = Self {
-            middleware,
-            driver,
-            rx_complete_fifo: vec![],
-            rx_incomplete_fifo: vec![],
-            #[cfg(feature="drvcan_v2")]
-            timeout: DEFAULT_TIMEOUT,
-            #[cfg(feature="drvcan_v2")]
-            retry_count: DEFAULT_RETRY_COUNT,
-        };
-        interface.init()?;
+#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
+impl CANInterface<TCAN455xTranceiver> {
+    pub fn new() -> Result<Self, Error> {
+        let driver: TCAN455xTranceiver = TCAN455xTranceiver::new()
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+        Self::with_backend(driver)
+    }
+
+    #[cfg(any(feature="raspberrypi", feature="raspberrypi_cm"))]
+    pub fn gpi_read(&mut self, channel: usize) -> bool {
+        self.driver.gpi_read(channel)
+    }
 
-        Ok(interface)
+    #[cfg(any(feature="raspberrypi", feature="raspberrypi_cm"))]
+    pub fn gpi_read_all(&mut self) -> [bool; GPIO_INPUT_PIN_NUM] {
+        self.driver.gpi_read_all()
     }
+}
 
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
 
-        self.driver.setup(&SIDF, &XIDF)?;
-        self.reset_rx_fifo();
+impl<B: CANBackend> CANInterface<B> {
+    /// Build an interface on top of an arbitrary CAN backend with the default configuration and initialize it.
+    pub fn with_backend(driver: B) -> Result<Self, Error> {
+        CANInterfaceBuilder::new().build(driver)
+    }
 
-        // Message: dummy transfer_id to make intentional missmatch of current_transfer_id in slaves and that in this system.
-        let now: u128 = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH).unwrap()
-            .as_millis();
-        let id_init: u8 = (now % 32) as u8;
-        self.middleware.transfer_id = id_init;
+    pub fn init(&mut self) -> Result<(), Error> {
+
+        self.driver.setup(&self.sidf, &self.xidf)?;
+        self.reset_rx_fifo();
+        self.subject_transfer_ids.clear();
+
+        self.middleware.transfer_id = match self.initial_transfer_id {
+            Some(transfer_id) => transfer_id,
+            None => {
+                // Message: dummy transfer_id to make intentional missmatch of current_transfer_id in slaves and that in this system.
+                let now: u128 = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH).unwrap()
+                    .as_millis();
+                (now % 32) as u8
+            }
+        };
 
         Ok(())
     }
 
-    #[cfg(all(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"), feature="drvcan_v2"))]
     pub fn set_timeout(&mut self, timeout: std::time::Duration) {
         self.timeout = timeout;
     }
 
-    #[cfg(all(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"), feature="drvcan_v2"))]
     pub fn set_retry_count(&mut self, retry_count: u32) {
         self.retry_count = retry_count;
     }
 
-    #[cfg(all(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"), feature="drvcan_v2"))]
     pub fn reset_settings(&mut self) {
         self.timeout = DEFAULT_TIMEOUT;
         self.retry_count = DEFAULT_RETRY_COUNT;
     }
 
-    #[cfg(any(feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn gpi_read(&mut self, channel: usize) -> bool {
-        self.driver.gpi_read(channel)
-    }
-
-    #[cfg(any(feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn gpi_read_all(&mut self) -> [bool; GPIO_INPUT_PIN_NUM] {
-        self.driver.gpi_read_all()
-    }
-
-
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
     pub fn reset_rx_fifo(&mut self) {
         self.rx_complete_fifo.clear();
         self.rx_incomplete_fifo.clear();
     }
 
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn send_message(&mut self, subject_id: u16, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        match self.middleware.create_message_data(subject_id, &payload, payload.len()) {
+    /// Publish on `subject_id`. Each subject has its own transfer ID counter, so messages on one subject
+    /// stay consecutive whatever else is sent in between.
+    pub fn send_message(&mut self, subject_id: u16, payload: &[u8]) -> Result<(), Error> {
+        let next_transfer_id: u8 = self.middleware.transfer_id;
+        self.middleware.transfer_id = self.subject_transfer_ids.get(&subject_id).copied().unwrap_or(next_transfer_id);
+        let packets = self.middleware.create_message_data(subject_id, payload, payload.len());
+        self.subject_transfer_ids.insert(subject_id, self.middleware.transfer_id);
+        self.middleware.transfer_id = next_transfer_id;
+
+        match packets {
             Ok(packets) => {
                 for packet in packets {
                     self.driver.transmit(packet.xid, &packet.payload, packet.payload_size)?
                 }
             },
-            Err(err) => return Err(err)
+            Err(err) => return Err(Error::serialization(err))
         }
         Ok(())
     }
 
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn send_response(&mut self, service_id: u16, channel: u8, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        match self.middleware.create_response_data(channel, service_id, &payload, payload.len()) {
+    pub fn send_response(&mut self, service_id: u16, channel: u8, payload: &[u8]) -> Result<(), Error> {
+        match self.middleware.create_response_data(channel, service_id, payload, payload.len()) {
             Ok(packets) => {
                 for packet in packets {
                     self.driver.transmit(packet.xid, &packet.payload, packet.payload_size)?
                 }
             },
-            Err(err) => return Err(err)
+            Err(err) => return Err(Error::serialization(err))
         }
         Ok(())
     }
 
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn send_request(&mut self, service_id: u16, channel: u8, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        match self.middleware.create_request_data(channel, service_id, &payload, payload.len()) {
+    pub fn send_request(&mut self, service_id: u16, channel: u8, payload: &[u8]) -> Result<(), Error> {
+        match self.middleware.create_request_data(channel, service_id, payload, payload.len()) {
             Ok(packets) => {
                 for packet in packets {
                     self.driver.transmit(packet.xid, &packet.payload, packet.payload_size)?
                 }
             },
-            Err(err) => return Err(err)
+            Err(err) => return Err(Error::serialization(err))
         }
         Ok(())
     }
 
+    /// Send a request and return the transfer ID its response will carry.
+    pub fn send_request_with_transfer_id(&mut self, service_id: u16, channel: u8, payload: &[u8]) -> Result<u8, Error> {
+        let transfer_id: u8 = self.middleware.transfer_id & CYPHAL_TRANSFER_ID_MAX;
+        self.send_request(service_id, channel, payload)?;
+        Ok(transfer_id)
+    }
+
+    /// Answer the request from `channel` that carried `transfer_id`, as a server must.
+    pub fn send_response_with_transfer_id(&mut self, service_id: u16, channel: u8, transfer_id: u8, payload: &[u8]) -> Result<(), Error> {
+        let next_transfer_id: u8 = self.middleware.transfer_id;
+        self.middleware.transfer_id = transfer_id;
+        let ret: Result<(), Error> = self.send_response(service_id, channel, payload);
+        self.middleware.transfer_id = next_transfer_id;
+        ret
+    }
+
+    /// Take an already received response from `channel` on `service_id` with `transfer_id`.
+    pub fn take_response(&mut self, service_id: u16, channel: u8, transfer_id: u8) -> Option<TimestampedRxFrame> {
+        self.take_response_on(&[service_id], channel, transfer_id)
+    }
+
+    /// Same as `take_response`, for servers that may answer on any of `service_ids`.
+    pub fn take_response_on(&mut self, service_ids: &[u16], channel: u8, transfer_id: u8) -> Option<TimestampedRxFrame> {
+        let position: usize = self.rx_complete_fifo.iter().position(|frame| {
+            (frame.props.transfer_kind == CyphalTransferKind::Response)
+                & service_ids.contains(&frame.props.port_id)
+                & (frame.props.source_node_id == channel)
+                & (frame.props.destination_node_id == self.node_id)
+                & (frame.props.transfer_id == transfer_id)
+        })?;
+        Some(self.rx_complete_fifo.remove(position))
+    }
+
+    /// Poll the device until the response from `channel` on `service_id` with `transfer_id` arrives or `timeout` elapses.
+    pub fn wait_response(&mut self, service_id: u16, channel: u8, transfer_id: u8, timeout: std::time::Duration) -> Result<Option<TimestampedRxFrame>, Error> {
+        self.wait_response_on(&[service_id], channel, transfer_id, timeout)
+    }
+
+    /// Same as `wait_response`, for servers that may answer on any of `service_ids`.
+    pub fn wait_response_on(&mut self, service_ids: &[u16], channel: u8, transfer_id: u8, timeout: std::time::Duration) -> Result<Option<TimestampedRxFrame>, Error> {
+        let deadline: std::time::Instant = std::time::Instant::now() + timeout;
+
+        loop {
+            self.load_frames()?;
+            if let Some(frame) = self.take_response_on(service_ids, channel, transfer_id) {
+                return Ok(Some(frame));
+            }
+            if std::time::Instant::now() >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(RESPONSE_POLLING_INTERVAL);
+        }
+    }
+
     /// Read received data from a FIFO buffer on a device.
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn read_device_fifo(&mut self) -> std::io::Result<Option<RxData>>{
+    pub fn read_device_fifo(&mut self) -> Result<Option<RxData>, Error> {
         match self.driver.receive() {
             Ok(rx_data) => Ok(rx_data),
-            Err(err) => Err(err)
+            Err(err) => Err(Error::Driver(err))
         }
     }
 
     /// Load cyphal frames from a FIFO buffer on a user space.
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn load_frames_from_buffer(&mut self, buffer: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// Multi-frame transfers are reassembled per session and transfer ID; rejected frames and CRC errors
+    /// are counted in `rx_stats` and do not fail the call.
+    pub fn load_frames_from_buffer(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        self.load_frames_from_buffer_at(buffer, std::time::Instant::now())
+    }
+
+    /// Same as `load_frames_from_buffer`, for a buffer read from the device at `timestamp`.
+    pub fn load_frames_from_buffer_at(&mut self, buffer: &[u8], timestamp: std::time::Instant) -> Result<(), Error> {
+        self.evict_stale_transfers(timestamp);
+        #[cfg(feature="drvcan_v2")]
+        let completed: usize = self.rx_complete_fifo.len();
+
         match self.middleware.try_read(buffer) {
             Ok(packets) => {
                 for packet in packets {
-                    match packet.status.frame_type {
-                        CyphalRxPacketType::SignleFrame => {
-                            self.rx_complete_fifo.push(CyphalRxFrame {
-                                xid: packet.xid,
-                                payload: packet.payload.to_vec(),
-                                payload_size: packet.payload_size,
-                                props: packet.props
-                            });
-                        },
-                        CyphalRxPacketType::MultiFrameStart => {
-                            self.rx_incomplete_fifo.push(CyphalRxFrame {
-                                xid: packet.xid,
-                                payload: Vec::from(&packet.payload[..packet.payload_size]),
-                                payload_size: packet.payload_size,
-                                props: packet.props
-                            });
-                        },
-                        CyphalRxPacketType::MultiFrameInProcess => {
-                            let target_frame_position: Option<usize> = self.rx_incomplete_fifo
-                                .iter()
-                                .position(|frame| (frame.xid == packet.xid) & (frame.props.port_id == packet.props.port_id));
-                            if let Some(position) = target_frame_position {
-                                self.rx_incomplete_fifo[position].payload.extend(&packet.payload[..packet.payload_size]);
-                                self.rx_incomplete_fifo[position].payload_size += packet.payload_size;
-                            }
-                        },
-                        CyphalRxPacketType::MultiFrameEnd => {
-                            let target_frame_position: Option<usize> = self.rx_incomplete_fifo
-                                .iter()
-                                .position(|frame: &CyphalRxFrame| (frame.xid == packet.xid) & (frame.props.port_id == packet.props.port_id));
-
-                            if let Some(position) = target_frame_position {
-                                self.rx_incomplete_fifo[position].payload.extend(&packet.payload[..(packet.payload_size - CRC_SIZE_BYTES as usize)]);
-                                self.rx_incomplete_fifo[position].payload_size += packet.payload_size - CRC_SIZE_BYTES as usize;
-
-                                let crc_bytes: [u8; 2] = self.rx_incomplete_fifo[position].calculate_crc()?;
-                                let crc_bytes_expected: [u8; 2] = [packet.payload[packet.payload_size - CRC_SIZE_BYTES as usize], packet.payload[packet.payload_size - CRC_SIZE_BYTES as usize + 1]];
-
-                                if crc_bytes == crc_bytes_expected {
-                                    self.rx_complete_fifo.push(self.rx_incomplete_fifo[position].clone());
-                                    self.rx_incomplete_fifo.remove(position);
-                                }
-                                else {
-                                    self.rx_incomplete_fifo.remove(position);
-                                    return Err("INVALID DATA EXIST: CRC ERROR AT MULTIFRAME CONSTRUCTION".into());
-                                }
-                            }
-                        }
-                    }
+                    self.reassemble(packet, timestamp);
                 }
             },
-            Err(err) => return Err(err)
+            Err(err) => return Err(Error::serialization(err))
         };
 
+        #[cfg(feature="drvcan_v2")]
+        self.track_faults(completed);
+
         Ok(())
     }
 
     /// Load cyphal frames from a FIFO buffer on a device.
     /// It wraps "read_device_fifo" and "load_frames_from_buffer"
-    #[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-    pub fn load_frames(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_frames(&mut self) -> Result<(), Error> {
+        let timestamp: std::time::Instant = std::time::Instant::now();
         let rx_data: Option<RxData> = self.read_device_fifo()?;
 
         if let Some(rx_data) = rx_data {
-            self.load_frames_from_buffer(&rx_data.fifo1)?
+            self.load_frames_from_buffer_at(&rx_data.fifo1, timestamp)?
         }
 
         Ok(())
@@ -0,0 +1,200 @@
+use std::time::Instant;
+
+use cands_transport::cyphal::{CyphalRxFrame, CyphalRxPacket, CyphalRxPacketType, CyphalRxProps, CRC_SIZE_BYTES};
+
+use crate::{CANBackend, CANInterface, MTU_CAN_FD};
+
+/// A multi-frame transfer whose end frame has not arrived yet.
+#[derive(Debug, Clone)]
+pub struct IncompleteTransfer {
+    /// Payload received so far, including the CRC bytes once they arrive.
+    pub frame: CyphalRxFrame,
+    /// Toggle bit expected on the next frame of this transfer.
+    pub toggle: bool,
+    /// Payload size of the last accepted frame, used to recognize duplicates.
+    pub last_frame_size: usize,
+    /// When the first frame of the transfer was read.
+    pub timestamp: Instant,
+}
+
+impl IncompleteTransfer {
+    /// Whether `props` belongs to this transfer: same session and same transfer ID.
+    pub fn matches(&self, props: &CyphalRxProps) -> bool {
+        let own: &CyphalRxProps = &self.frame.props;
+        (own.source_node_id == props.source_node_id)
+            & (own.destination_node_id == props.destination_node_id)
+            & (own.port_id == props.port_id)
+            & (own.transfer_kind == props.transfer_kind)
+            & (own.transfer_id == props.transfer_id)
+    }
+
+    fn is_duplicate(&self, payload: &[u8]) -> bool {
+        let received: &[u8] = &self.frame.payload;
+        (self.last_frame_size == payload.len()) && received.ends_with(payload)
+    }
+}
+
+/// A completed transfer and the time its first frame was read from the device.
+#[derive(Debug, Clone)]
+pub struct TimestampedRxFrame {
+    pub frame: CyphalRxFrame,
+    pub timestamp: Instant,
+}
+
+impl std::ops::Deref for TimestampedRxFrame {
+    type Target = CyphalRxFrame;
+
+    fn deref(&self) -> &Self::Target {
+        &self.frame
+    }
+}
+
+/// Decoded data of a completed transfer, with its receive timestamp.
+#[derive(Debug, Clone)]
+pub struct TimestampedRxData<T> {
+    pub data: T,
+    pub props: CyphalRxProps,
+    pub timestamp: Instant,
+}
+
+/// Counters of received frames rejected during reassembly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RxStats {
+    /// Multi-frame transfers whose CRC did not match.
+    pub crc_errors: u64,
+    /// Frames whose toggle bit broke the expected order; the transfer is dropped.
+    pub toggle_errors: u64,
+    /// Frames received twice in a row, dropped.
+    pub duplicate_frames: u64,
+    /// Continuation or end frames without a matching start frame.
+    pub orphan_frames: u64,
+    /// Incomplete transfers discarded because a new start frame arrived for the same transfer.
+    pub interrupted_transfers: u64,
+    /// End frames too short to hold the transfer CRC.
+    pub malformed_transfers: u64,
+    /// Incomplete transfers dropped after `transfer_id_timeout`.
+    pub timed_out_transfers: u64,
+    /// Incomplete transfers dropped to stay within `max_incomplete_transfers`.
+    pub evicted_transfers: u64,
+}
+
+impl<B: CANBackend> CANInterface<B> {
+    pub fn reset_rx_stats(&mut self) {
+        self.rx_stats = RxStats::default();
+    }
+
+    /// Drop incomplete transfers whose first frame is older than `transfer_id_timeout` at `now`.
+    pub fn evict_stale_transfers(&mut self, now: Instant) {
+        let timeout: std::time::Duration = self.transfer_id_timeout;
+        let count: usize = self.rx_incomplete_fifo.len();
+        self.rx_incomplete_fifo.retain(|transfer| now.saturating_duration_since(transfer.timestamp) < timeout);
+        self.rx_stats.timed_out_transfers += (count - self.rx_incomplete_fifo.len()) as u64;
+    }
+
+    /// Feed one received frame into the per-session reassembly.
+    /// Rejected frames and transfers are only counted in `rx_stats`, so a corrupt transfer from one node
+    /// does not hold back the frames received from others.
+    pub(crate) fn reassemble(&mut self, packet: CyphalRxPacket<MTU_CAN_FD>, timestamp: Instant) {
+        let payload: &[u8] = &packet.payload[..packet.payload_size];
+
+        if packet.status.frame_type == CyphalRxPacketType::SignleFrame {
+            let frame: CyphalRxFrame = CyphalRxFrame {
+                xid: packet.xid,
+                payload: packet.payload.to_vec(),
+                payload_size: packet.payload_size,
+                props: packet.props
+            };
+            self.rx_complete_fifo.push(TimestampedRxFrame { frame, timestamp });
+            return;
+        }
+
+        let target_position: Option<usize> = self.rx_incomplete_fifo
+            .iter()
+            .position(|transfer| transfer.matches(&packet.props));
+
+        if packet.status.frame_type == CyphalRxPacketType::MultiFrameStart {
+            if let Some(position) = target_position {
+                match self.rx_incomplete_fifo[position].frame.payload[..] == payload[..] {
+                    true => {
+                        self.rx_stats.duplicate_frames += 1;
+                        return;
+                    },
+                    false => {
+                        self.rx_incomplete_fifo.remove(position);
+                        self.rx_stats.interrupted_transfers += 1;
+                    }
+                }
+            }
+
+            // The first frame of a transfer always has the toggle bit set.
+            if !packet.status.toggle {
+                self.rx_stats.toggle_errors += 1;
+                return;
+            }
+
+            // Transfers are pushed in arrival order, so the first one is the oldest.
+            while !self.rx_incomplete_fifo.is_empty() && (self.rx_incomplete_fifo.len() >= self.max_incomplete_transfers) {
+                self.rx_incomplete_fifo.remove(0);
+                self.rx_stats.evicted_transfers += 1;
+            }
+
+            self.rx_incomplete_fifo.push(IncompleteTransfer {
+                frame: CyphalRxFrame {
+                    xid: packet.xid,
+                    payload: Vec::from(payload),
+                    payload_size: packet.payload_size,
+                    props: packet.props
+                },
+                toggle: false,
+                last_frame_size: packet.payload_size,
+                timestamp,
+            });
+            return;
+        }
+
+        let position: usize = match target_position {
+            Some(position) => position,
+            None => {
+                self.rx_stats.orphan_frames += 1;
+                return;
+            }
+        };
+
+        if packet.status.toggle != self.rx_incomplete_fifo[position].toggle {
+            match self.rx_incomplete_fifo[position].is_duplicate(payload) {
+                true => self.rx_stats.duplicate_frames += 1,
+                false => {
+                    self.rx_incomplete_fifo.remove(position);
+                    self.rx_stats.toggle_errors += 1;
+                }
+            }
+            return;
+        }
+
+        let transfer: &mut IncompleteTransfer = &mut self.rx_incomplete_fifo[position];
+        transfer.frame.payload.extend(payload);
+        transfer.frame.payload_size += packet.payload_size;
+        transfer.toggle = !transfer.toggle;
+        transfer.last_frame_size = packet.payload_size;
+
+        if packet.status.frame_type == CyphalRxPacketType::MultiFrameEnd {
+            let IncompleteTransfer { mut frame, timestamp: first_timestamp, .. } = self.rx_incomplete_fifo.remove(position);
+
+            // The transfer CRC may be split over the last two frames.
+            let crc_size: usize = CRC_SIZE_BYTES as usize;
+            if frame.payload_size < crc_size {
+                self.rx_stats.malformed_transfers += 1;
+                return;
+            }
+            let crc_bytes_expected: Vec<u8> = frame.payload.split_off(frame.payload_size - crc_size);
+            frame.payload_size -= crc_size;
+
+            match frame.calculate_crc() {
+                Ok(crc_bytes) if crc_bytes[..] == crc_bytes_expected[..] => {
+                    self.rx_complete_fifo.push(TimestampedRxFrame { frame, timestamp: first_timestamp });
+                },
+                _ => self.rx_stats.crc_errors += 1
+            }
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use cands_transport::cyphal::{CyphalTransferKind, CYPHAL_TRANSFER_ID_MAX};
+use futures_lite::Stream;
+use tokio::sync::{broadcast, oneshot};
+
+use crate::{CANBackend, CANInterface, Error, TimestampedRxFrame};
+use crate::uavcan::{HeartbeatPublisher, TimeSyncPublisher};
+
+#[cfg(feature="drvcan_v2")]
+use cands_presentation::cyphal::digitalservo::dictionary::Dict;
+#[cfg(feature="drvcan_v2")]
+use crate::TimestampedRxData;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+const RX_CHANNEL_CAPACITY: usize = 256;
+
+type Filter<T> = Box<dyn FnMut(&TimestampedRxFrame) -> Option<T> + Send>;
+
+/// In-flight requests keyed by (server node ID, transfer ID).
+type PendingRequests = Arc<Mutex<HashMap<(u8, u8), oneshot::Sender<TimestampedRxFrame>>>>;
+
+type SharedHeartbeat = Arc<Mutex<Option<HeartbeatPublisher>>>;
+
+type SharedTimeSync = Arc<Mutex<Option<TimeSyncPublisher>>>;
+
+/// Everything the reader thread shares with the `CANRuntime` handle.
+struct Reader<B> {
+    interface: Arc<Mutex<CANInterface<B>>>,
+    sender: broadcast::Sender<TimestampedRxFrame>,
+    pending: PendingRequests,
+    heartbeat: SharedHeartbeat,
+    time_sync: SharedTimeSync,
+    error: Arc<Mutex<Option<Error>>>,
+    running: Arc<AtomicBool>,
+    poll_interval: Duration,
+}
+
+/// A `CANInterface` whose receive side is owned by a background reader thread.
+///
+/// Every completed transfer is broadcast to all subscribers, so consumers wait
+/// for data without polling and without taking frames from each other.
+/// Transmit through `interface()`; while the runtime runs, receive only through subscriptions.
+///
+/// The backend is read on a dedicated thread, so a slow device read never blocks the tokio executor.
+/// Backend errors do not stop the reader: it keeps retrying and keeps the last error for `take_reader_error`.
+///
+/// Requests sent with `request` may run concurrently from several tasks;
+/// each response is routed back to its caller by server node ID and transfer ID.
+pub struct CANRuntime<B: CANBackend> {
+    interface: Arc<Mutex<CANInterface<B>>>,
+    sender: broadcast::Sender<TimestampedRxFrame>,
+    pending: PendingRequests,
+    heartbeat: SharedHeartbeat,
+    time_sync: SharedTimeSync,
+    error: Arc<Mutex<Option<Error>>>,
+    running: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl<B: CANBackend + Send + 'static> CANRuntime<B> {
+    /// Start the reader thread. The reader is a plain thread, so no tokio runtime is needed here.
+    pub fn spawn(interface: CANInterface<B>) -> Self {
+        Self::spawn_with_poll_interval(interface, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Start the reader thread, checking the device FIFO every `poll_interval` once it is empty.
+    pub fn spawn_with_poll_interval(interface: CANInterface<B>, poll_interval: Duration) -> Self {
+        let interface: Arc<Mutex<CANInterface<B>>> = Arc::new(Mutex::new(interface));
+        let (sender, _) = broadcast::channel(RX_CHANNEL_CAPACITY);
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let heartbeat: SharedHeartbeat = Arc::new(Mutex::new(None));
+        let time_sync: SharedTimeSync = Arc::new(Mutex::new(None));
+        let error: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+
+        let reader: Reader<B> = Reader {
+            interface: interface.clone(),
+            sender: sender.clone(),
+            pending: pending.clone(),
+            heartbeat: heartbeat.clone(),
+            time_sync: time_sync.clone(),
+            error: error.clone(),
+            running: running.clone(),
+            poll_interval,
+        };
+        let reader: JoinHandle<()> = std::thread::Builder::new()
+            .name("cands_cyphal-reader".into())
+            .spawn(move || reader.run())
+            .expect("failed to spawn the CAN reader thread");
+
+        Self { interface, sender, pending, heartbeat, time_sync, error, running, reader: Some(reader) }
+    }
+
+    /// Stop the reader thread and hand the interface back. Must be awaited on a tokio runtime.
+    ///
+    /// The interface is returned only once no other holder remains, so this waits
+    /// for background tasks (e.g. a PnP or file server) that are transmitting.
+    pub async fn stop(mut self) -> CANInterface<B> {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            // The thread finishes its current poll, which may block on the backend.
+            let _ = tokio::task::spawn_blocking(move || reader.join()).await;
+        }
+
+        let mut interface: Arc<Mutex<CANInterface<B>>> = self.interface.clone();
+        drop(self);
+
+        // Background tasks only upgrade their `Weak` handle while transmitting.
+        loop {
+            match Arc::try_unwrap(interface) {
+                Ok(interface) => return interface.into_inner().unwrap_or_else(|err| err.into_inner()),
+                Err(shared) => {
+                    interface = shared;
+                    tokio::time::sleep(DEFAULT_POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+}
+
+impl<B: CANBackend> Reader<B> {
+    fn run(self) {
+        while self.running.load(Ordering::Relaxed) {
+            match self.poll() {
+                Ok((node_id, frames)) => self.dispatch(node_id, frames),
+                Err(err) => {
+                    #[cfg(feature="log")]
+                    log::warn!(target: "cands_cyphal::runtime", "CAN receive failed, retrying: {}", err);
+                    *self.error.lock().unwrap_or_else(|err| err.into_inner()) = Some(err);
+                }
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Run the periodic publishers and drain the device FIFO.
+    fn poll(&self) -> Result<(u8, Vec<TimestampedRxFrame>), Error> {
+        let mut interface: MutexGuard<CANInterface<B>> = lock(&self.interface);
+
+        if let Some(publisher) = self.heartbeat.lock().unwrap_or_else(|err| err.into_inner()).as_mut() {
+            // A failed heartbeat is retried on the next period.
+            let _ = publisher.tick(&mut interface);
+        }
+        if let Some(publisher) = self.time_sync.lock().unwrap_or_else(|err| err.into_inner()).as_mut() {
+            let _ = publisher.tick(&mut interface);
+        }
+
+        loop {
+            let timestamp: Instant = Instant::now();
+            // On failure, frames completed so far stay in `rx_complete_fifo` for the next poll.
+            match interface.read_device_fifo()? {
+                Some(rx_data) => {
+                    // Rejected frames are counted in `rx_stats`.
+                    let _ = interface.load_frames_from_buffer_at(&rx_data.fifo1, timestamp);
+                },
+                None => break
+            }
+        }
+        Ok((interface.node_id, std::mem::take(&mut interface.rx_complete_fifo)))
+    }
+
+    /// Hand responses to their waiting requests and broadcast every frame.
+    fn dispatch(&self, node_id: u8, frames: Vec<TimestampedRxFrame>) {
+        if !frames.is_empty() {
+            let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+            for frame in frames.iter().filter(|frame| (frame.props.transfer_kind == CyphalTransferKind::Response) & (frame.props.destination_node_id == node_id)) {
+                if let Some(waiter) = pending.remove(&(frame.props.source_node_id, frame.props.transfer_id)) {
+                    let _ = waiter.send(frame.clone());
+                }
+            }
+        }
+
+        for frame in frames {
+            // Sending only fails when nobody is subscribed.
+            let _ = self.sender.send(frame);
+        }
+    }
+}
+
+impl<B: CANBackend> CANRuntime<B> {
+    /// Lock the interface, e.g. to send requests.
+    pub fn interface(&self) -> MutexGuard<'_, CANInterface<B>> {
+        lock(&self.interface)
+    }
+
+    /// Take the last error the reader thread got from the backend, if any.
+    pub fn take_reader_error(&self) -> Option<Error> {
+        self.error.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+
+    /// A handle for background tasks that transmit on their own. Upgrade it only for one transmission,
+    /// so `stop` can take the interface back.
+    pub(crate) fn weak_interface(&self) -> Weak<Mutex<CANInterface<B>>> {
+        Arc::downgrade(&self.interface)
+    }
+
+    /// Send a request and wait up to `timeout` for the response with the same transfer ID.
+    ///
+    /// Returns `None` if no response arrived in time.
+    /// The response is also broadcast to subscribers.
+    /// Fails with `Error::TransferIdInUse` without transmitting if the transfer ID has wrapped around
+    /// onto a request to the same node that is still waiting.
+    pub async fn request(&self, service_id: u16, channel: u8, payload: &[u8], timeout: Duration) -> Result<Option<TimestampedRxFrame>, Error> {
+        let (waiter, response) = oneshot::channel();
+
+        let guard: PendingGuard = {
+            let mut interface: MutexGuard<CANInterface<B>> = self.interface();
+            let transfer_id: u8 = interface.middleware.transfer_id & CYPHAL_TRANSFER_ID_MAX;
+            let guard: PendingGuard = PendingGuard::register(&self.pending, (channel, transfer_id), waiter)?;
+            interface.send_request(service_id, channel, payload)?;
+            guard
+        };
+
+        let ret: Result<Option<TimestampedRxFrame>, Error> = match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(frame)) => Ok(Some(frame)),
+            Ok(Err(_)) => Err(Error::Closed),
+            Err(_) => Ok(None),
+        };
+        drop(guard);
+
+        ret
+    }
+
+    /// Publish heartbeats from the reader thread until `stop_heartbeat` is called.
+    pub fn start_heartbeat(&self, publisher: HeartbeatPublisher) {
+        *self.heartbeat.lock().unwrap_or_else(|err| err.into_inner()) = Some(publisher);
+    }
+
+    pub fn stop_heartbeat(&self) -> Option<HeartbeatPublisher> {
+        self.heartbeat.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+
+    /// Change the running heartbeat publisher, e.g. its health or mode.
+    pub fn update_heartbeat<R>(&self, f: impl FnOnce(&mut HeartbeatPublisher) -> R) -> Option<R> {
+        self.heartbeat.lock().unwrap_or_else(|err| err.into_inner()).as_mut().map(f)
+    }
+
+    /// Publish time synchronization messages from the reader thread until `stop_time_sync` is called.
+    pub fn start_time_sync(&self, publisher: TimeSyncPublisher) {
+        *self.time_sync.lock().unwrap_or_else(|err| err.into_inner()) = Some(publisher);
+    }
+
+    pub fn stop_time_sync(&self) -> Option<TimeSyncPublisher> {
+        self.time_sync.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+
+    /// Receive every completed transfer.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<TimestampedRxFrame> {
+        self.sender.subscribe()
+    }
+
+    /// Receive completed transfers on `port_id`.
+    pub fn subscribe(&self, port_id: u16) -> Subscription<TimestampedRxFrame> {
+        self.subscribe_with(move |frame| (frame.props.port_id == port_id).then(|| frame.clone()))
+    }
+
+    /// Receive the transfers for which `filter` returns a value.
+    pub fn subscribe_with<T, F>(&self, filter: F) -> Subscription<T>
+    where
+        F: FnMut(&TimestampedRxFrame) -> Option<T> + Send + 'static
+    {
+        Subscription { receiver: self.sender.subscribe(), filter: Box::new(filter) }
+    }
+
+    /// Receive DigitalServo key-value data, optionally restricted to one key and one source node.
+    #[cfg(feature="drvcan_v2")]
+    pub fn subscribe_digitalservo(&self, key: Option<&str>, source_node_id: Option<u8>) -> Subscription<TimestampedRxData<Dict>> {
+        const TARGET_PORT_ID: [u16; 3] = [128, 129, 1160];
+        let key: Option<String> = key.map(String::from);
+
+        self.subscribe_with(move |frame| {
+            if !TARGET_PORT_ID.contains(&frame.props.port_id) {
+                return None;
+            }
+            if source_node_id.is_some_and(|node_id| frame.props.source_node_id != node_id) {
+                return None;
+            }
+            let data: Dict = Dict::deserialize(&frame.payload).ok()?;
+            if key.as_ref().is_some_and(|key| data.key != *key) {
+                return None;
+            }
+            Some(TimestampedRxData { data, props: frame.props, timestamp: frame.timestamp })
+        })
+    }
+}
+
+impl<B: CANBackend> Drop for CANRuntime<B> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Drops pending requests whose callers stopped waiting, including on cancellation.
+struct PendingGuard<'a> {
+    pending: &'a Mutex<HashMap<(u8, u8), oneshot::Sender<TimestampedRxFrame>>>,
+}
+
+impl<'a> PendingGuard<'a> {
+    fn register(pending: &'a PendingRequests, key: (u8, u8), waiter: oneshot::Sender<TimestampedRxFrame>) -> Result<Self, Error> {
+        let mut requests = pending.lock().unwrap_or_else(|err| err.into_inner());
+        if requests.get(&key).is_some_and(|waiter| !waiter.is_closed()) {
+            return Err(Error::TransferIdInUse { channel: key.0, transfer_id: key.1 });
+        }
+        requests.insert(key, waiter);
+        Ok(Self { pending })
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap_or_else(|err| err.into_inner()).retain(|_, waiter| !waiter.is_closed());
+    }
+}
+
+fn lock<B>(interface: &Mutex<CANInterface<B>>) -> MutexGuard<'_, CANInterface<B>> {
+    interface.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Filtered view of the transfers broadcast by a `CANRuntime`.
+pub struct Subscription<T> {
+    receiver: broadcast::Receiver<TimestampedRxFrame>,
+    filter: Filter<T>,
+}
+
+impl<T> Subscription<T> {
+    /// Wait for the next matching transfer.
+    /// Transfers dropped because this subscriber lagged behind are skipped.
+    pub async fn recv(&mut self) -> Result<T, Error> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(frame) => {
+                    if let Some(value) = (self.filter)(&frame) {
+                        return Ok(value);
+                    }
+                },
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Err(Error::Closed),
+            }
+        }
+    }
+
+    /// Return a matching transfer if one is already queued.
+    pub fn try_recv(&mut self) -> Option<T> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(frame) => {
+                    if let Some(value) = (self.filter)(&frame) {
+                        return Some(value);
+                    }
+                },
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = T> {
+        futures_lite::stream::unfold(self, |mut subscription| async move {
+            subscription.recv().await.ok().map(|value| (value, subscription))
+        })
+    }
+}
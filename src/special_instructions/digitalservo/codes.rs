@@ -0,0 +1,119 @@
+use std::fmt;
+
+// Only zero has a meaning this crate relies on: a set-value request succeeded, or a drive reports no error.
+// The meaning of the other codes is firmware-specific, so they are kept as raw values.
+
+/// Result code a drive replies on port 0x87 to a set-value or get-value request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultCode {
+    Ok,
+    Other(u8),
+}
+
+impl ResultCode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ResultCode::Ok => "accepted",
+            ResultCode::Other(_) => "rejected by the drive",
+        }
+    }
+}
+
+impl From<u8> for ResultCode {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => ResultCode::Ok,
+            x => ResultCode::Other(x)
+        }
+    }
+}
+
+impl From<ResultCode> for u8 {
+    fn from(x: ResultCode) -> Self {
+        match x {
+            ResultCode::Ok => 0,
+            ResultCode::Other(x) => x
+        }
+    }
+}
+
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:#04x})", self.description(), u8::from(*self))
+    }
+}
+
+/// Error code a drive publishes on subject 0x17C0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    NoError,
+    Other(u8),
+}
+
+impl ErrorCode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::NoError => "no error",
+            ErrorCode::Other(_) => "drive error",
+        }
+    }
+}
+
+impl From<u8> for ErrorCode {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => ErrorCode::NoError,
+            x => ErrorCode::Other(x)
+        }
+    }
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(x: ErrorCode) -> Self {
+        match x {
+            ErrorCode::NoError => 0,
+            ErrorCode::Other(x) => x
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:#04x})", self.description(), u8::from(*self))
+    }
+}
+
+/// General status of the drives: the latest result code received on port 0x87, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneralStatus {
+    NoData,
+    Result(ResultCode),
+}
+
+impl GeneralStatus {
+    pub fn description(&self) -> &'static str {
+        match self {
+            GeneralStatus::NoData => "no result received",
+            GeneralStatus::Result(code) => code.description(),
+        }
+    }
+}
+
+/// `NoData` converts to 0xFF, the value the raw status used for it.
+impl From<GeneralStatus> for u8 {
+    fn from(x: GeneralStatus) -> Self {
+        match x {
+            GeneralStatus::NoData => 0xFF,
+            GeneralStatus::Result(code) => code.into()
+        }
+    }
+}
+
+impl fmt::Display for GeneralStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneralStatus::NoData => write!(f, "{}", self.description()),
+            GeneralStatus::Result(code) => write!(f, "{}", code)
+        }
+    }
+}
@@ -1,3 +1,9 @@
+mod codes;
+pub use codes::{ErrorCode, GeneralStatus, ResultCode};
+
+mod state;
+pub use state::DriveState;
+
 #[cfg(feature="drvcan_v1")]
 pub mod v1;
 
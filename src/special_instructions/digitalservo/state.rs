@@ -0,0 +1,26 @@
+use std::fmt;
+
+/// Power stage state of one axis, as tracked by `drive_enable` and `drive_disable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DriveState {
+    #[default]
+    Disabled,
+    Enabling,
+    Enabled,
+    /// The drive reported an error; only `drive_disable` is accepted.
+    Faulted,
+    Disabling,
+}
+
+impl fmt::Display for DriveState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state: &str = match self {
+            DriveState::Disabled => "disabled",
+            DriveState::Enabling => "enabling",
+            DriveState::Enabled => "enabled",
+            DriveState::Faulted => "faulted",
+            DriveState::Disabling => "disabling",
+        };
+        f.write_str(state)
+    }
+}
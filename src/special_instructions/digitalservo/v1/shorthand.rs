@@ -1,10 +1,8 @@
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use std::{thread, time};
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-impl crate::CANInterface {
+impl<B: crate::CANBackend> crate::CANInterface<B> {
 
-    pub fn drive_enable(&mut self, channel: u8) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn drive_enable(&mut self, channel: u8) -> Result<(), crate::Error> {
 
         self.send_digitalservo_response(channel, "cmdval", &[0.0])?;
         thread::sleep(time::Duration::from_millis(50));
@@ -15,7 +13,7 @@ impl crate::CANInterface {
         Ok(())
     }
 
-    pub fn drive_enable_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn drive_enable_all(&mut self) -> Result<(), crate::Error> {
 
         self.send_digitalservo_message("cmdval", &[0.0])?;
         thread::sleep(time::Duration::from_millis(50));
@@ -26,7 +24,7 @@ impl crate::CANInterface {
         Ok(())
     }
 
-    pub fn drive_disable(&mut self, channel: u8) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn drive_disable(&mut self, channel: u8) -> Result<(), crate::Error> {
 
         self.send_digitalservo_response(channel, "drive", &[0.0])?;
         thread::sleep(time::Duration::from_millis(100));
@@ -37,7 +35,7 @@ impl crate::CANInterface {
         Ok(())
     }
 
-    pub fn drive_disable_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn drive_disable_all(&mut self) -> Result<(), crate::Error> {
         
         self.send_digitalservo_message("drive", &[0.0])?;
         thread::sleep(time::Duration::from_millis(50));
@@ -48,11 +46,11 @@ impl crate::CANInterface {
         Ok(())
     }
 
-    pub fn send_velocity_reference(&mut self, channel: u8, value: f64) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn send_velocity_reference(&mut self, channel: u8, value: f64) -> Result<(), crate::Error> {
         self.send_digitalservo_response(channel, "cmdval", &[value])
     }
 
-    pub fn send_motion_reference(&mut self, channel: u8, value: &[f64; 4]) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn send_motion_reference(&mut self, channel: u8, value: &[f64; 4]) -> Result<(), crate::Error> {
         self.send_digitalservo_response(channel, "cmdarray", value)
     }
 }
\ No newline at end of file
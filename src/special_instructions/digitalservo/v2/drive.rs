@@ -0,0 +1,312 @@
+use std::time::{Duration, Instant};
+
+use crate::digitalservo::{DriveState, ErrorCode};
+use crate::Subscription;
+
+use super::fault::{reset_key, ERROR_SUBJECT_ID};
+use super::parameter::validate;
+use super::{CmdArray, CmdVal, Drive, ErrorEvent, Parameter, CMDARRAY_LEN};
+
+/// First non-zero error code received by `errors` until `deadline`.
+async fn settle_fault(errors: &mut Subscription<ErrorEvent>, deadline: tokio::time::Instant) -> Option<ErrorCode> {
+    let fault = async {
+        loop {
+            match errors.recv().await {
+                Ok(error) if error.code != ErrorCode::NoError => return Some(error.code),
+                Ok(_) => continue,
+                Err(_) => return None
+            }
+        }
+    };
+    tokio::time::timeout_at(deadline, fault).await.unwrap_or(None)
+}
+
+/// What `drive_enable` has to do from `state`.
+fn enable_from(channel: u8, state: DriveState) -> Result<bool, crate::Error> {
+    match state {
+        DriveState::Disabled => Ok(true),
+        DriveState::Enabled => Ok(false),
+        state => Err(crate::Error::InvalidDriveState { channel, state, action: "enable".into() })
+    }
+}
+
+/// Whether `clear_fault` is accepted from `state`.
+fn clear_fault_from(channel: u8, state: DriveState) -> Result<(), crate::Error> {
+    match state {
+        DriveState::Faulted | DriveState::Disabled => Ok(()),
+        state => Err(crate::Error::InvalidDriveState { channel, state, action: "clear a fault".into() })
+    }
+}
+
+/// State reached once the fault reset went through.
+fn confirm_clear_fault(channel: u8, fault: Option<ErrorCode>) -> (DriveState, Result<(), crate::Error>) {
+    match fault {
+        Some(code) => (DriveState::Faulted, Err(crate::Error::DriveFault { channel, code })),
+        None => (DriveState::Disabled, Ok(()))
+    }
+}
+
+/// State reached once the enable sequence went through.
+fn confirm_enable(channel: u8, drive: bool, fault: Option<ErrorCode>) -> (DriveState, Result<(), crate::Error>) {
+    match (fault, drive) {
+        (Some(code), _) => (DriveState::Faulted, Err(crate::Error::DriveFault { channel, code })),
+        (None, true) => (DriveState::Enabled, Ok(())),
+        (None, false) => (DriveState::Disabled, Err(crate::Error::InvalidDriveState {
+            channel,
+            state: DriveState::Disabled,
+            action: "enable: drive stayed off".into()
+        }))
+    }
+}
+
+/// State left behind by an enable sequence that failed half-way.
+fn abort_enable(state: DriveState) -> DriveState {
+    match state {
+        DriveState::Faulted => DriveState::Faulted,
+        _ => DriveState::Disabled
+    }
+}
+
+/// State left behind by a disable sequence that failed half-way: `previous`, unless the drive reported an error.
+fn abort_disable(previous: DriveState, state: DriveState) -> DriveState {
+    match state {
+        DriveState::Faulted => DriveState::Faulted,
+        _ => previous
+    }
+}
+
+/// State reached once the disable sequence went through.
+fn confirm_disable(channel: u8, drive: bool) -> (DriveState, Result<(), crate::Error>) {
+    match drive {
+        false => (DriveState::Disabled, Ok(())),
+        true => (DriveState::Disabling, Err(crate::Error::InvalidDriveState {
+            channel,
+            state: DriveState::Disabling,
+            action: "disable: drive stayed on".into()
+        }))
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    pub fn drive_state(&self, channel: u8) -> DriveState {
+        self.drive_states.get(&channel).copied().unwrap_or_default()
+    }
+
+    /// Write `P` without the motion check, for the enable and disable sequences.
+    fn write<P: Parameter>(&mut self, channel: u8, value: P::Value) -> Result<(), crate::Error> {
+        let elements = validate::<P>(&value)?;
+        self.send_digitalservo_set_value(channel, P::NAME, &elements)
+    }
+
+    /// Move the axes that published a non-zero error code since `rx_complete_fifo[first]` to `Faulted`.
+    /// The frames stay in the FIFO for `get_error`.
+    pub(crate) fn track_faults(&mut self, first: usize) {
+        for frame in self.rx_complete_fifo.iter().skip(first) {
+            if (frame.props.port_id == ERROR_SUBJECT_ID) && frame.payload[..frame.payload_size].first().is_some_and(|code| *code != 0) {
+                self.drive_states.insert(frame.props.source_node_id, DriveState::Faulted);
+            }
+        }
+    }
+
+    /// First non-zero error code received from `channel`.
+    fn take_fault(&mut self, channel: u8) -> Result<Option<ErrorCode>, crate::Error> {
+        let errors = self.get_error(Some(channel))?.unwrap_or_default();
+        Ok(errors.into_iter().map(|error| error.data).find(|code| *code != ErrorCode::NoError))
+    }
+
+    /// First non-zero error code received from `channel` until `fault_settle_time` after `since`.
+    fn settle_fault(&mut self, channel: u8, since: Instant) -> Result<Option<ErrorCode>, crate::Error> {
+        let deadline: Instant = since + self.fault_settle_time;
+        loop {
+            if let Some(code) = self.take_fault(channel)? {
+                return Ok(Some(code));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(crate::RESPONSE_POLLING_INTERVAL);
+        }
+    }
+
+    /// Fail unless `channel` is `Enabled`, after taking in the errors received so far.
+    pub(crate) fn check_motion(&mut self, channel: u8, key: &str) -> Result<(), crate::Error> {
+        self.load_frames()?;
+        match self.drive_state(channel) {
+            DriveState::Enabled => Ok(()),
+            state => Err(crate::Error::InvalidDriveState { channel, state, action: format!("send \"{}\"", key) })
+        }
+    }
+
+    /// Zero the commands, switch the power stage on and read `drive` back, then listen to the error port
+    /// for `fault_settle_time` after switching on. Only a `Disabled` axis can be enabled. If the sequence fails half-way the axis goes back to `Disabled`,
+    /// or `Faulted` if the drive reported an error; `drive_disable` makes sure the power stage is off.
+    pub fn drive_enable(&mut self, channel: u8) -> Result<(), crate::Error> {
+        if !enable_from(channel, self.drive_state(channel))? {
+            return Ok(());
+        }
+        // Errors latched before the transition are not ours.
+        let _ = self.take_fault(channel)?;
+        self.drive_states.insert(channel, DriveState::Enabling);
+
+        let mut sequence = || -> Result<(), crate::Error> {
+            self.write::<CmdVal>(channel, 0.0)?;
+            self.write::<CmdArray>(channel, [0.0; CMDARRAY_LEN])?;
+            self.write::<Drive>(channel, true)?;
+            let since: Instant = Instant::now();
+            let drive: bool = self.get::<Drive>(channel)?;
+            let (state, ret) = confirm_enable(channel, drive, self.settle_fault(channel, since)?);
+            self.drive_states.insert(channel, state);
+            ret
+        };
+
+        let ret: Result<(), crate::Error> = sequence();
+        if ret.is_err() {
+            let state: DriveState = abort_enable(self.drive_state(channel));
+            self.drive_states.insert(channel, state);
+        }
+        ret
+    }
+
+    /// Switch the power stage off, zero the commands and read `drive` back. Accepted in any state.
+    /// If the sequence fails half-way the axis goes back to its previous state, or `Faulted` if the drive reported an error.
+    pub fn drive_disable(&mut self, channel: u8) -> Result<(), crate::Error> {
+        let previous: DriveState = self.drive_state(channel);
+        self.drive_states.insert(channel, DriveState::Disabling);
+
+        let mut sequence = || -> Result<bool, crate::Error> {
+            self.write::<Drive>(channel, false)?;
+            self.write::<CmdVal>(channel, 0.0)?;
+            self.write::<CmdArray>(channel, [0.0; CMDARRAY_LEN])?;
+            self.get::<Drive>(channel)
+        };
+
+        match sequence() {
+            Ok(drive) => {
+                let (state, ret) = confirm_disable(channel, drive);
+                self.drive_states.insert(channel, state);
+                ret
+            },
+            Err(err) => {
+                let state: DriveState = abort_disable(previous, self.drive_state(channel));
+                self.drive_states.insert(channel, state);
+                Err(err)
+            }
+        }
+    }
+
+    /// Reset a latched fault by writing `true` to `FaultKeys::reset` and return the axis to `Disabled`, without a power cycle.
+    /// Accepted while `Faulted` or `Disabled`; a fault reported again within `fault_settle_time` of the reset
+    /// keeps the axis `Faulted`.
+    pub fn clear_fault(&mut self, channel: u8) -> Result<(), crate::Error> {
+        clear_fault_from(channel, self.drive_state(channel))?;
+        let key: String = reset_key(&self.fault_keys)?;
+        // Errors received so far belong to the fault being cleared.
+        let _ = self.take_fault(channel)?;
+
+        self.send_digitalservo_set_value(channel, &key, &[true])?;
+        let since: Instant = Instant::now();
+
+        let (state, ret) = confirm_clear_fault(channel, self.settle_fault(channel, since)?);
+        self.drive_states.insert(channel, state);
+        ret
+    }
+}
+
+impl<B: crate::CANBackend + Send + 'static> crate::CANRuntime<B> {
+    pub fn drive_state(&self, channel: u8) -> DriveState {
+        self.interface().drive_state(channel)
+    }
+
+    fn set_drive_state(&self, channel: u8, state: DriveState) {
+        self.interface().drive_states.insert(channel, state);
+    }
+
+    fn fault_settle_time(&self) -> Duration {
+        self.interface().fault_settle_time
+    }
+
+    async fn write<P: Parameter>(&self, channel: u8, value: P::Value) -> Result<(), crate::Error> {
+        let elements = validate::<P>(&value)?;
+        self.send_digitalservo_set_value(channel, P::NAME, &elements).await
+    }
+
+    /// Fail unless `channel` is `Enabled`. Errors are taken in by the reader as they arrive.
+    pub(crate) fn check_motion(&self, channel: u8, key: &str) -> Result<(), crate::Error> {
+        match self.drive_state(channel) {
+            DriveState::Enabled => Ok(()),
+            state => Err(crate::Error::InvalidDriveState { channel, state, action: format!("send \"{}\"", key) })
+        }
+    }
+
+    /// Same as `CANInterface::drive_enable`, awaiting each step without blocking the thread.
+    pub async fn drive_enable(&self, channel: u8) -> Result<(), crate::Error> {
+        {
+            let mut interface = self.interface();
+            if !enable_from(channel, interface.drive_state(channel))? {
+                return Ok(());
+            }
+            interface.drive_states.insert(channel, DriveState::Enabling);
+        }
+        let mut errors: Subscription<ErrorEvent> = self.subscribe_errors(Some(channel));
+
+        let sequence = async {
+            self.write::<CmdVal>(channel, 0.0).await?;
+            self.write::<CmdArray>(channel, [0.0; CMDARRAY_LEN]).await?;
+            self.write::<Drive>(channel, true).await?;
+            let deadline: tokio::time::Instant = tokio::time::Instant::now() + self.fault_settle_time();
+            let drive: bool = self.get::<Drive>(channel).await?;
+            let (state, ret) = confirm_enable(channel, drive, settle_fault(&mut errors, deadline).await);
+            self.set_drive_state(channel, state);
+            ret
+        };
+
+        let ret: Result<(), crate::Error> = sequence.await;
+        if ret.is_err() {
+            self.set_drive_state(channel, abort_enable(self.drive_state(channel)));
+        }
+        ret
+    }
+
+    /// Same as `CANInterface::drive_disable`, awaiting each step without blocking the thread.
+    pub async fn drive_disable(&self, channel: u8) -> Result<(), crate::Error> {
+        let previous: DriveState = {
+            let mut interface = self.interface();
+            let previous: DriveState = interface.drive_state(channel);
+            interface.drive_states.insert(channel, DriveState::Disabling);
+            previous
+        };
+
+        let sequence = async {
+            self.write::<Drive>(channel, false).await?;
+            self.write::<CmdVal>(channel, 0.0).await?;
+            self.write::<CmdArray>(channel, [0.0; CMDARRAY_LEN]).await?;
+            self.get::<Drive>(channel).await
+        };
+
+        match sequence.await {
+            Ok(drive) => {
+                let (state, ret) = confirm_disable(channel, drive);
+                self.set_drive_state(channel, state);
+                ret
+            },
+            Err(err) => {
+                self.set_drive_state(channel, abort_disable(previous, self.drive_state(channel)));
+                Err(err)
+            }
+        }
+    }
+
+    /// Same as `CANInterface::clear_fault`, awaiting the reply without blocking the thread.
+    pub async fn clear_fault(&self, channel: u8) -> Result<(), crate::Error> {
+        clear_fault_from(channel, self.drive_state(channel))?;
+        let key: String = reset_key(&self.interface().fault_keys)?;
+        let mut errors: Subscription<ErrorEvent> = self.subscribe_errors(Some(channel));
+
+        self.send_digitalservo_set_value(channel, &key, &[true]).await?;
+        let deadline: tokio::time::Instant = tokio::time::Instant::now() + self.fault_settle_time();
+
+        let (state, ret) = confirm_clear_fault(channel, settle_fault(&mut errors, deadline).await);
+        self.set_drive_state(channel, state);
+        ret
+    }
+}
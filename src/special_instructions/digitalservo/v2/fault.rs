@@ -0,0 +1,114 @@
+use std::time::Instant;
+
+use cands_presentation::cyphal::digitalservo::dictionary::DigitalServoPrimitiveData;
+
+use crate::digitalservo::ErrorCode;
+use crate::{Subscription, TimestampedRxData};
+
+pub(crate) const ERROR_SUBJECT_ID: u16 = 0x17C0;
+
+/// Keys `clear_fault` and `read_error_history` use. They are not part of the v2 protocol and depend on
+/// the drive firmware, so none is set by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaultKeys {
+    /// Written with `true` to clear a latched fault.
+    pub reset: Option<String>,
+    /// Error codes the drive keeps, oldest first. Unused slots read as 0.
+    pub history: Option<String>,
+}
+
+impl FaultKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_reset(mut self, key: impl Into<String>) -> Self {
+        self.reset = Some(key.into());
+        self
+    }
+
+    pub fn with_history(mut self, key: impl Into<String>) -> Self {
+        self.history = Some(key.into());
+        self
+    }
+}
+
+fn configured(key: &Option<String>, what: &str) -> Result<String, crate::Error> {
+    key.clone().ok_or_else(|| crate::Error::InvalidConfig(format!("no {} key configured; it depends on the drive firmware", what)))
+}
+
+pub(crate) fn reset_key(keys: &FaultKeys) -> Result<String, crate::Error> {
+    configured(&keys.reset, "fault reset")
+}
+
+fn history_key(keys: &FaultKeys) -> Result<String, crate::Error> {
+    configured(&keys.history, "error history")
+}
+
+/// An error code published by a drive on 0x17C0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorEvent {
+    pub timestamp: Instant,
+    pub node_id: u8,
+    pub code: ErrorCode,
+}
+
+impl From<TimestampedRxData<ErrorCode>> for ErrorEvent {
+    fn from(x: TimestampedRxData<ErrorCode>) -> Self {
+        Self { timestamp: x.timestamp, node_id: x.props.source_node_id, code: x.data }
+    }
+}
+
+fn decode_history(channel: u8, key: &str, value: &[DigitalServoPrimitiveData]) -> Result<Vec<ErrorCode>, crate::Error> {
+    value.iter()
+        .map(|code| u8::try_from(code.clone()).map(ErrorCode::from))
+        .filter(|code| !matches!(code, Ok(ErrorCode::NoError)))
+        .collect::<Result<Vec<ErrorCode>, _>>()
+        .map_err(|_| crate::Error::TypeConversion { key: format!("{} of node {}", key, channel) })
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    pub fn set_fault_keys(&mut self, fault_keys: FaultKeys) {
+        self.fault_keys = fault_keys;
+    }
+
+    pub fn set_fault_settle_time(&mut self, settle_time: std::time::Duration) {
+        self.fault_settle_time = settle_time;
+    }
+
+    /// Read the error codes kept by the drive under `FaultKeys::history`, oldest first, skipping unused slots.
+    pub fn read_error_history(&mut self, channel: u8) -> Result<Vec<ErrorCode>, crate::Error> {
+        let key: String = history_key(&self.fault_keys)?;
+        let data = self.send_digitalservo_get_value(channel, &key)?;
+        match data.last() {
+            Some(data) => decode_history(channel, &key, &data.data.value),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Take received error codes as events, optionally only those of one node.
+    pub fn get_error_events(&mut self, source_node_id: Option<u8>) -> Result<Vec<ErrorEvent>, crate::Error> {
+        let errors = self.get_error(source_node_id)?.unwrap_or_default();
+        Ok(errors.into_iter().map(ErrorEvent::from).collect())
+    }
+}
+
+impl<B: crate::CANBackend + Send + 'static> crate::CANRuntime<B> {
+    /// Same as `CANInterface::read_error_history`, awaiting the reply without blocking the thread.
+    pub async fn read_error_history(&self, channel: u8) -> Result<Vec<ErrorCode>, crate::Error> {
+        let key: String = history_key(&self.interface().fault_keys)?;
+        let data = self.send_digitalservo_get_value(channel, &key).await?;
+        decode_history(channel, &key, &data.data.value)
+    }
+
+    /// Receive the error codes published by the drives, optionally only those of one node.
+    pub fn subscribe_errors(&self, source_node_id: Option<u8>) -> Subscription<ErrorEvent> {
+        self.subscribe_with(move |frame| {
+            if (frame.props.port_id != ERROR_SUBJECT_ID) || source_node_id.is_some_and(|node_id| frame.props.source_node_id != node_id) {
+                return None;
+            }
+            let code: ErrorCode = frame.payload[..frame.payload_size].first().copied()?.into();
+            Some(ErrorEvent { timestamp: frame.timestamp, node_id: frame.props.source_node_id, code })
+        })
+    }
+}
@@ -1,5 +1,14 @@
 mod request_sync;
 mod requst_async;
 mod read;
+mod runtime;
 
 mod shorthand;
+
+mod drive;
+
+mod fault;
+pub use fault::{ErrorEvent, FaultKeys};
+
+mod parameter;
+pub use parameter::{lookup, Access, CmdArray, CmdVal, Drive, Parameter, ParameterInfo, ParameterType, ParameterValue, Readable, Scalar, Writable, CATALOG, CMDARRAY_LEN};
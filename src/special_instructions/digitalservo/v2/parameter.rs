@@ -0,0 +1,272 @@
+use cands_presentation::cyphal::digitalservo::dictionary::{DigitalServoPrimitiveData, IntoDigitalServoDataType};
+
+/// Element type of a DigitalServo parameter on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// Whether a parameter can be read with get-value, written with set-value, or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    pub fn readable(self) -> bool {
+        self != Access::WriteOnly
+    }
+
+    pub fn writable(self) -> bool {
+        self != Access::ReadOnly
+    }
+}
+
+/// Description of a DigitalServo parameter, as listed in `CATALOG`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterInfo {
+    pub name: &'static str,
+    pub data_type: ParameterType,
+    /// Number of elements; scalars have 1.
+    pub arity: usize,
+    /// Physical unit, empty if the parameter has none or it depends on the control mode.
+    pub unit: &'static str,
+    pub access: Access,
+    /// Inclusive bounds checked on every element before transmission.
+    pub range: Option<(f64, f64)>,
+}
+
+impl ParameterInfo {
+    /// Check `value` against the arity and range of the parameter.
+    pub fn validate(&self, value: &[f64]) -> Result<(), crate::Error> {
+        let invalid = |reason: String| crate::Error::InvalidValue { key: self.name.into(), reason };
+
+        if value.len() != self.arity {
+            return Err(invalid(format!("expected {} elements, got {}", self.arity, value.len())));
+        }
+        if let Some((min, max)) = self.range {
+            if let Some(x) = value.iter().find(|x| !(min..=max).contains(*x)) {
+                return Err(invalid(format!("{} is out of range [{}, {}]", x, min, max)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Element of a parameter value.
+pub trait Scalar: Copy + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData> + TryFrom<DigitalServoPrimitiveData> {
+    const TYPE: ParameterType;
+
+    /// Value used for range checks.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_scalar {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl Scalar for $ty {
+                const TYPE: ParameterType = ParameterType::$variant;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, i8 => I8, i16 => I16, i32 => I32, i64 => I64, f32 => F32, f64 => F64);
+
+impl Scalar for bool {
+    const TYPE: ParameterType = ParameterType::Bool;
+
+    fn to_f64(self) -> f64 {
+        self as u8 as f64
+    }
+}
+
+/// Rust type of a whole parameter value: a `Scalar` or a fixed-size array of them.
+pub trait ParameterValue: Sized {
+    type Element: Scalar;
+    const ARITY: usize;
+
+    fn elements(&self) -> Vec<Self::Element>;
+
+    /// `None` if the element count or type does not match.
+    fn from_elements(elements: &[DigitalServoPrimitiveData]) -> Option<Self>;
+}
+
+impl<T: Scalar> ParameterValue for T {
+    type Element = T;
+    const ARITY: usize = 1;
+
+    fn elements(&self) -> Vec<T> {
+        vec![*self]
+    }
+
+    fn from_elements(elements: &[DigitalServoPrimitiveData]) -> Option<Self> {
+        match elements {
+            [element] => T::try_from(element.clone()).ok(),
+            _ => None
+        }
+    }
+}
+
+impl<T: Scalar, const N: usize> ParameterValue for [T; N] {
+    type Element = T;
+    const ARITY: usize = N;
+
+    fn elements(&self) -> Vec<T> {
+        self.to_vec()
+    }
+
+    fn from_elements(elements: &[DigitalServoPrimitiveData]) -> Option<Self> {
+        let elements: Vec<T> = elements.iter().map(|element| T::try_from(element.clone()).ok()).collect::<Option<Vec<T>>>()?;
+        elements.try_into().ok()
+    }
+}
+
+/// A DigitalServo parameter known at compile time. Implement it, together with `Readable` and/or `Writable`,
+/// to use drive-specific parameters with `CANInterface::set` and `CANInterface::get`.
+pub trait Parameter {
+    type Value: ParameterValue;
+    const NAME: &'static str;
+    const UNIT: &'static str = "";
+    const ACCESS: Access;
+    const RANGE: Option<(f64, f64)> = None;
+    /// Motion commands are only sent to an `Enabled` axis.
+    const MOTION: bool = false;
+
+    const INFO: ParameterInfo = ParameterInfo {
+        name: Self::NAME,
+        data_type: <<Self::Value as ParameterValue>::Element as Scalar>::TYPE,
+        arity: <Self::Value as ParameterValue>::ARITY,
+        unit: Self::UNIT,
+        access: Self::ACCESS,
+        range: Self::RANGE,
+    };
+}
+
+/// Marks a `Parameter` that accepts get-value requests.
+pub trait Readable: Parameter {}
+
+/// Marks a `Parameter` that accepts set-value requests.
+pub trait Writable: Parameter {}
+
+/// `drive`: power stage on or off.
+pub struct Drive;
+
+impl Parameter for Drive {
+    type Value = bool;
+    const NAME: &'static str = "drive";
+    const ACCESS: Access = Access::ReadWrite;
+}
+impl Readable for Drive {}
+impl Writable for Drive {}
+
+/// Any finite `f64`. Unit and limits of the command values depend on the control mode, so only NaN and
+/// infinities are rejected before transmission.
+const FINITE: Option<(f64, f64)> = Some((f64::MIN, f64::MAX));
+
+/// Number of elements of `cmdarray`.
+pub const CMDARRAY_LEN: usize = 16;
+
+/// `cmdval`: command value of the active control mode.
+pub struct CmdVal;
+
+impl Parameter for CmdVal {
+    type Value = f64;
+    const NAME: &'static str = "cmdval";
+    const ACCESS: Access = Access::ReadWrite;
+    const RANGE: Option<(f64, f64)> = FINITE;
+    const MOTION: bool = true;
+}
+impl Readable for CmdVal {}
+impl Writable for CmdVal {}
+
+/// `cmdarray`: command values of the active control mode, one per element.
+pub struct CmdArray;
+
+impl Parameter for CmdArray {
+    type Value = [f64; CMDARRAY_LEN];
+    const NAME: &'static str = "cmdarray";
+    const ACCESS: Access = Access::ReadWrite;
+    const RANGE: Option<(f64, f64)> = FINITE;
+    const MOTION: bool = true;
+}
+impl Readable for CmdArray {}
+impl Writable for CmdArray {}
+
+/// Parameters every DigitalServo v2 drive understands.
+pub const CATALOG: &[ParameterInfo] = &[Drive::INFO, CmdVal::INFO, CmdArray::INFO];
+
+/// Look up a parameter of `CATALOG` by key.
+pub fn lookup(name: &str) -> Option<&'static ParameterInfo> {
+    CATALOG.iter().find(|info| info.name == name)
+}
+
+pub(crate) fn validate<P: Parameter>(value: &P::Value) -> Result<Vec<<P::Value as ParameterValue>::Element>, crate::Error> {
+    let elements: Vec<<P::Value as ParameterValue>::Element> = value.elements();
+    P::INFO.validate(&elements.iter().map(|x| x.to_f64()).collect::<Vec<f64>>())?;
+    Ok(elements)
+}
+
+pub(crate) fn decode<P: Parameter>(elements: &[DigitalServoPrimitiveData]) -> Result<P::Value, crate::Error> {
+    P::Value::from_elements(elements).ok_or_else(|| crate::Error::TypeConversion { key: P::NAME.into() })
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Typed `send_digitalservo_set_value`. The value is checked against the arity and range of `P` before transmission,
+    /// and motion commands are rejected unless the axis is `Enabled`.
+    pub fn set<P: Writable>(&mut self, channel: u8, value: P::Value) -> Result<(), crate::Error> {
+        let elements = validate::<P>(&value)?;
+        if P::MOTION {
+            self.check_motion(channel, P::NAME)?;
+        }
+        self.send_digitalservo_set_value(channel, P::NAME, &elements)
+    }
+
+    /// Typed `send_digitalservo_message` to every drive. Not checked against the state of the axes.
+    pub fn broadcast<P: Writable>(&mut self, value: P::Value) -> Result<(), crate::Error> {
+        let elements = validate::<P>(&value)?;
+        self.send_digitalservo_message(P::NAME, &elements)
+    }
+
+    /// Typed `send_digitalservo_get_value`.
+    pub fn get<P: Readable>(&mut self, channel: u8) -> Result<P::Value, crate::Error> {
+        let data = self.send_digitalservo_get_value(channel, P::NAME)?;
+        match data.last() {
+            Some(data) => decode::<P>(&data.data.value),
+            None => Err(crate::Error::TypeConversion { key: P::NAME.into() })
+        }
+    }
+}
+
+impl<B: crate::CANBackend + Send + 'static> crate::CANRuntime<B> {
+    /// Same as `CANInterface::set`, awaiting the reply without blocking the thread.
+    pub async fn set<P: Writable>(&self, channel: u8, value: P::Value) -> Result<(), crate::Error> {
+        let elements = validate::<P>(&value)?;
+        if P::MOTION {
+            self.check_motion(channel, P::NAME)?;
+        }
+        self.send_digitalservo_set_value(channel, P::NAME, &elements).await
+    }
+
+    /// Same as `CANInterface::get`, awaiting the reply without blocking the thread.
+    pub async fn get<P: Readable>(&self, channel: u8) -> Result<P::Value, crate::Error> {
+        let data = self.send_digitalservo_get_value(channel, P::NAME).await?;
+        decode::<P>(&data.data.value)
+    }
+}
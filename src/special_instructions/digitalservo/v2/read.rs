@@ -1,12 +1,10 @@
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-use cands_transport::cyphal::CyphalRxData;
+use crate::TimestampedRxData;
+use crate::digitalservo::{ErrorCode, GeneralStatus, ResultCode};
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use cands_presentation::cyphal::digitalservo::dictionary::Dict;
 
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-impl crate::CANInterface {
+impl<B: crate::CANBackend> crate::CANInterface<B> {
 
     pub fn clear_rx_complete_fifo(&mut self) {
         self.rx_complete_fifo.clear();
@@ -16,7 +14,8 @@ impl crate::CANInterface {
         self.rx_incomplete_fifo.clear();
     }
 
-    pub fn get_digitalservo_general_status(&mut self) -> u8 {
+    /// Latest result code on 0x87 in the receive FIFO, `GeneralStatus::NoData` if there is none.
+    pub fn get_digitalservo_general_status(&mut self) -> GeneralStatus {
         const TARGET_PORT_ID: u16 = 0x87;
         // Filter data which are to be processed
         let mut target_ids: Vec<usize> = vec![];
@@ -27,19 +26,18 @@ impl crate::CANInterface {
             }
         }
 
-        let mut result :u8 = 0xFF;
+        let mut result: GeneralStatus = GeneralStatus::NoData;
 
         // Process target data
         for process_target_id in &target_ids {
             let packet = &self.rx_complete_fifo[*process_target_id];
             // match Dict::deserialize(&packet.payload) {
-            //     Ok(data) => v.push(CyphalRxData{data, props: packet.props}),
+            //     Ok(data) => v.push(TimestampedRxData{data, props: packet.props, timestamp: packet.timestamp}),
             //     Err(err) => return Err(err)
             // }
             // v.push(&packet.payload);
-            match packet.payload.first() {
-                Some(val) => result = *val,
-                None => ()
+            if let Some(val) = packet.payload.first() {
+                result = GeneralStatus::Result(ResultCode::from(*val));
             }
 
         }
@@ -53,10 +51,10 @@ impl crate::CANInterface {
     }
 
 
-    pub fn get_key_value(&mut self, key: Option<&str>, source_node_id: Option<u8>) -> Result<Option<Vec<CyphalRxData<Dict>>>, Box<dyn std::error::Error>> {
+    pub fn get_key_value(&mut self, key: Option<&str>, source_node_id: Option<u8>) -> Result<Option<Vec<TimestampedRxData<Dict>>>, crate::Error> {
         const TARGET_PORT_ID: [u16; 3] = [128, 129, 1160];
 
-        let mut buffer: Vec<CyphalRxData<Dict>> = Vec::new();
+        let mut buffer: Vec<TimestampedRxData<Dict>> = Vec::new();
 
         // Load data from a device FIFO and put RxFrames on a user-space FIFO
         self.load_frames()?;
@@ -80,7 +78,7 @@ impl crate::CANInterface {
                 let get_flag = get_flag && if let Some(source_node_id) = source_node_id { packet.props.source_node_id == source_node_id } else { true };
 
                 if get_flag {
-                    buffer.push(CyphalRxData{data, props: packet.props});
+                    buffer.push(TimestampedRxData{data, props: packet.props, timestamp: packet.timestamp});
                     remove_ids.push(*process_target_id);
                 }
             }
@@ -99,10 +97,10 @@ impl crate::CANInterface {
 
     }
 
-    pub fn get_result(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<CyphalRxData<u8>>>, Box<dyn std::error::Error>> {
+    pub fn get_result(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<TimestampedRxData<ResultCode>>>, crate::Error> {
         const TARGET_PORT_ID: u16 = 0x87;
 
-        let mut buffer: Vec<CyphalRxData<u8>> = Vec::new();
+        let mut buffer: Vec<TimestampedRxData<ResultCode>> = Vec::new();
 
         // Load data from a device FIFO and put RxFrames on a user-space FIFO
         self.load_frames()?;
@@ -124,7 +122,7 @@ impl crate::CANInterface {
             let get_flag = if let Some(source_node_id) = source_node_id { packet.props.source_node_id == source_node_id } else { true };
 
             if get_flag {
-                buffer.push(CyphalRxData{data: packet.payload[0], props: packet.props});
+                buffer.push(TimestampedRxData{data: packet.payload[0].into(), props: packet.props, timestamp: packet.timestamp});
                 remove_ids.push(*process_target_id);
             }
         }
@@ -141,10 +139,10 @@ impl crate::CANInterface {
     }
 
 
-    pub fn get_error(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<CyphalRxData<u8>>>, Box<dyn std::error::Error>> {
+    pub fn get_error(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<TimestampedRxData<ErrorCode>>>, crate::Error> {
         const TARGET_PORT_ID: u16 = 0x17C0;
 
-        let mut buffer: Vec<CyphalRxData<u8>> = Vec::new();
+        let mut buffer: Vec<TimestampedRxData<ErrorCode>> = Vec::new();
 
         // Load data from a device FIFO and put RxFrames on a user-space FIFO
         self.load_frames()?;
@@ -166,7 +164,7 @@ impl crate::CANInterface {
             let get_flag = if let Some(source_node_id) = source_node_id { packet.props.source_node_id == source_node_id } else { true };
 
             if get_flag {
-                buffer.push(CyphalRxData{data: packet.payload[0], props: packet.props});
+                buffer.push(TimestampedRxData{data: packet.payload[0].into(), props: packet.props, timestamp: packet.timestamp});
                 remove_ids.push(*process_target_id);
             }
         }
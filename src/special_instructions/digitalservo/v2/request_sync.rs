@@ -1,17 +1,41 @@
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use cands_presentation::cyphal::digitalservo::{
     dictionary::{Dict, DigitalServoPrimitiveData, IntoDigitalServoDataType},
     string::Str,
 };
 
-use cands_transport::cyphal::CyphalRxData;
-use futures_lite::FutureExt;
-use async_io::{block_on, Timer};
+use crate::{TimestampedRxData, TimestampedRxFrame};
+use crate::digitalservo::ResultCode;
 
-const CHECK_FIFO_POLLING_MS: u64 = 2;
+pub(crate) const RESULT_PORT_ID: u16 = 0x87;
+pub(crate) const VALUE_PORT_ID: u16 = 0x80;
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-impl crate::CANInterface {
+/// Outcome of a set-value reply: `None` if the frame carries no result code.
+pub(crate) fn set_value_reply(channel: u8, key: &str, frame: &TimestampedRxFrame) -> Option<Result<(), crate::Error>> {
+    // A rejection is final; only a missing reply is retried.
+    match frame.payload[..frame.payload_size].first().map(|code| ResultCode::from(*code))? {
+        ResultCode::Ok => Some(Ok(())),
+        code => Some(Err(crate::Error::DriveResult { channel, key: key.into(), code }))
+    }
+}
+
+/// Outcome of a get-value reply, which is either the value on 0x80 or a result code on 0x87.
+/// `None` if the frame answers nothing useful and the request should be retried.
+pub(crate) fn get_value_reply(channel: u8, key: &str, frame: &TimestampedRxFrame) -> Option<Result<TimestampedRxData<Dict>, crate::Error>> {
+    match frame.props.port_id {
+        VALUE_PORT_ID => match Dict::deserialize(&frame.payload) {
+            Ok(data) if data.key == key => Some(Ok(TimestampedRxData { data, props: frame.props, timestamp: frame.timestamp })),
+            Ok(_) => None,
+            Err(err) => Some(Err(crate::Error::serialization(err)))
+        },
+        RESULT_PORT_ID => match set_value_reply(channel, key, frame)? {
+            Ok(()) => None,
+            Err(err) => Some(Err(err))
+        },
+        _ => None
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
 
     /// [DEPRECATED IN RELIABLE PROCESS]
     /// 
@@ -23,7 +47,7 @@ impl crate::CANInterface {
         &mut self,
         key: &str,
         value: &[T]
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<(), crate::Error>
     where
         T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData>
     {
@@ -44,12 +68,12 @@ impl crate::CANInterface {
         channel: u8,
         key: &str,
         value: &[T]
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<(), crate::Error>
     where
         T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData>
     {
         const SERVICE_ID: u16 = 0x80;
-        let payload:Vec<u8> = Dict::serialize(key, &value);
+        let payload:Vec<u8> = Dict::serialize(key, value);
         self.send_response(SERVICE_ID, channel, &payload)
     }
 
@@ -60,7 +84,7 @@ impl crate::CANInterface {
     /// 
     /// in the worst case, the request would lost when a child node failed to receive a packet due to insufficient processing capacity.
     /// 
-    pub fn send_digitalservo_request(&mut self, channel: u8, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn send_digitalservo_request(&mut self, channel: u8, key: &str) -> Result<(), crate::Error> {
         const SERVICE_ID: u16 = 0x80;
         let payload:Vec<u8> = Dict::serialize(key, &[0.0]);
         self.send_request(SERVICE_ID, channel, &payload)
@@ -70,10 +94,11 @@ impl crate::CANInterface {
     /// 
     /// In case of communication failure (e.g., a child node failed to receive), this function would retry to send a message.
     /// If no acknowledge signal returns within the specified number of times, this function returns error.
+    /// A non-zero result code is not retried and returns `Error::DriveResult` at once.
     /// 
     /// A timeout for each trial and the limit number of retries are in Self::timeout and Self::retry_count.
     /// These can be set by
-    /// ```
+    /// ```ignore
     /// self.set_retry_count(retry_count);
     /// self.set_timeout(timeout);
     /// ```
@@ -83,53 +108,26 @@ impl crate::CANInterface {
         channel: u8,
         key: &str,
         value: &[T],
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<(), crate::Error>
         where
             T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData>
     {
         const SERVICE_ID: u16 = 0x81;
-        let payload:Vec<u8> = Dict::serialize(key, &value);
+        let payload:Vec<u8> = Dict::serialize(key, value);
 
         let timeout = self.timeout;
 
         for _ in 0..self.retry_count {
+            let transfer_id: u8 = self.send_request_with_transfer_id(SERVICE_ID, channel, &payload)?;
 
-            self.send_request(SERVICE_ID, channel, &payload)?;
-
-            let ret: Result<(), ()> = {
-                let task = async {
-                    loop {
-                        let _ = self.load_frames();
-                        let results = match self.get_result(Some(channel)) {
-                            Ok(ret) => ret,
-                            Err(_) => continue,
-                        };
-
-                        if let Some(results) = results {
-                            if results.iter().all(|y| y.data == 0) {
-                                return Ok(())
-                            }
-                        }
-                        Timer::after(std::time::Duration::from_millis(CHECK_FIFO_POLLING_MS)).await;
-                    }
-
-                };
-                
-                let timeout_handler = async {
-                    Timer::after(timeout).await;
-                    Err(())
-                };
-            
-                block_on(task.or(timeout_handler))
-            };
-
-            if let Ok(success) = ret {
-                return Ok(success)
+            if let Some(frame) = self.wait_response(RESULT_PORT_ID, channel, transfer_id, timeout)? {
+                if let Some(ret) = set_value_reply(channel, key, &frame) {
+                    return ret;
+                }
             }
         }
 
-        let err: std::io::Error = std::io::ErrorKind::TimedOut.into();
-        Err(err.into())
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: self.retry_count })
 
     }
 
@@ -142,20 +140,21 @@ impl crate::CANInterface {
     /// It can be used as non-blocking process.
     /// Use this only in cyclic operation and get data in after the cycle.
     /// 
-    pub fn send_digitalservo_get_value_request(&mut self, channel: u8, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn send_digitalservo_get_value_request(&mut self, channel: u8, key: &str) -> Result<(), crate::Error> {
         const SERVICE_ID: u16 = 0x82;
         let payload:Vec<u8> = Str::serialize(key);
         self.send_request(SERVICE_ID, channel, &payload)
     }
 
     /// It requires a child node replying data when it successfully receive a message.
+    /// The reply is matched by transfer ID, and a non-zero result code returns `Error::DriveResult` at once.
     /// 
     /// In case of communication failure (e.g., a child node failed to receive), this function would retry to send a message.
     /// If no acknowledge signal returns within the specified number of times, this function returns error.
     /// 
     /// A timeout for each trial and the limit number of retries are in Self::timeout and Self::retry_count.
     /// These can be set by
-    /// ```
+    /// ```ignore
     /// self.set_retry_count(retry_count);
     /// self.set_timeout(timeout);
     /// ```
@@ -164,7 +163,7 @@ impl crate::CANInterface {
         &mut self,
         channel: u8,
         key: &str,
-    ) -> Result<Vec<CyphalRxData<Dict>>, Box<dyn std::error::Error>> {
+    ) -> Result<Vec<TimestampedRxData<Dict>>, crate::Error> {
 
         const SERVICE_ID: u16 = 0x82;
         let payload:Vec<u8> = Str::serialize(key);
@@ -172,39 +171,16 @@ impl crate::CANInterface {
         let timeout = self.timeout;
 
         for _ in 0..self.retry_count {
+            let transfer_id: u8 = self.send_request_with_transfer_id(SERVICE_ID, channel, &payload)?;
 
-            self.send_request(SERVICE_ID, channel, &payload)?;
-
-            let ret: Result<Vec<CyphalRxData<Dict>>, ()> = {
-                let task = async {
-                    loop {
-                        let results = match self.get_key_value(Some(key), Some(channel)) {
-                            Ok(ret) => ret,
-                            Err(_) => continue,
-                        };
-
-                        if let Some(results) = results {
-                            return Ok(results)
-                        }
-                        Timer::after(std::time::Duration::from_millis(CHECK_FIFO_POLLING_MS)).await;
-                    }
-                };
-
-                let timeout_handler = async {
-                    Timer::after(timeout).await;
-                    Err(())
-                };
-            
-                block_on(task.or(timeout_handler))
-            };
-            
-            if let Ok(success) = ret {
-                return Ok(success)
+            if let Some(frame) = self.wait_response_on(&[VALUE_PORT_ID, RESULT_PORT_ID], channel, transfer_id, timeout)? {
+                if let Some(ret) = get_value_reply(channel, key, &frame) {
+                    return ret.map(|data| vec![data]);
+                }
             }
         }
 
-        let err: std::io::Error = std::io::ErrorKind::TimedOut.into();
-        Err(err.into())
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: self.retry_count })
 
     }
 
@@ -1,73 +1,61 @@
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use cands_presentation::cyphal::digitalservo::{
     dictionary::{Dict, DigitalServoPrimitiveData, IntoDigitalServoDataType},
     string::Str,
 };
-use cands_transport::cyphal::CyphalRxData;
+use crate::{TimestampedRxData, TimestampedRxFrame};
 use tokio::time::error::Elapsed;
 
+use super::request_sync::{get_value_reply, set_value_reply, RESULT_PORT_ID, VALUE_PORT_ID};
+
 const CHECK_FIFO_POLLING_MS: u64 = 2;
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-impl crate::CANInterface {
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+
+    /// Poll the device without blocking the thread until the response with `transfer_id` arrives.
+    async fn async_wait_response_on(&mut self, service_ids: &[u16], channel: u8, transfer_id: u8) -> Result<TimestampedRxFrame, crate::Error> {
+        loop {
+            self.load_frames()?;
+            if let Some(frame) = self.take_response_on(service_ids, channel, transfer_id) {
+                return Ok(frame)
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(CHECK_FIFO_POLLING_MS)).await;
+        }
+    }
 
     pub async fn async_send_digitalservo_set_value<T>(
         &mut self,
         channel: u8,
         key: &str,
         value: &[T],
-    ) -> Result<(), Box<dyn std::error::Error>>
+    ) -> Result<(), crate::Error>
     where
         T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData>
     {
         const SERVICE_ID: u16 = 0x81;
+        let payload:Vec<u8> = Dict::serialize(key, value);
 
         let timeout = self.timeout;
 
         for _ in 0..self.retry_count {
-            let ret: Result<Result<(), Box<dyn std::error::Error>>, Elapsed> = {
-                
-                let task = async {
-                    let payload:Vec<u8> = Dict::serialize(key, &value);
-                    self.send_request(SERVICE_ID, channel, &payload)?;
-
-                    loop {
-                        let _ = self.load_frames();
-                        let results = match self.get_result(Some(channel)) {
-                            Ok(ret) => ret,
-                            Err(_) => continue,
-                        };
-
-                        if let Some(results) = results {
-                            if results.iter().all(|y| y.data == 0) {
-                                return Ok(())
-                            }
-                        }
-
-                        tokio::time::sleep(std::time::Duration::from_millis(CHECK_FIFO_POLLING_MS)).await;
-                    }
-                };
-
-                tokio::time::timeout(timeout, task).await
-            };
-
-            if let Ok(ret) = ret {
-                match ret {
-                    Ok(success) => return Ok(success),
-                    Err(e) => return Err(e)
+            let transfer_id: u8 = self.send_request_with_transfer_id(SERVICE_ID, channel, &payload)?;
+            let ret: Result<Result<TimestampedRxFrame, crate::Error>, Elapsed> = tokio::time::timeout(timeout, self.async_wait_response_on(&[RESULT_PORT_ID], channel, transfer_id)).await;
+
+            if let Ok(frame) = ret {
+                if let Some(ret) = set_value_reply(channel, key, &frame?) {
+                    return ret;
                 }
             }
         }
 
-        let err: std::io::Error = std::io::ErrorKind::TimedOut.into();
-        Err(err.into())
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: self.retry_count })
     }
 
     pub async fn async_send_digitalservo_get_value(
         &mut self,
         channel: u8,
         key: &str,
-    ) -> Result<Vec<CyphalRxData<Dict>>, Box<dyn std::error::Error>> {
+    ) -> Result<Vec<TimestampedRxData<Dict>>, crate::Error> {
         
         const SERVICE_ID: u16 = 0x82;
         let payload:Vec<u8> = Str::serialize(key);
@@ -75,39 +63,17 @@ impl crate::CANInterface {
         let timeout = self.timeout;
 
         for _ in 0..self.retry_count {
-            let ret: Result<Result<Vec<CyphalRxData<Dict>>, Box<dyn std::error::Error>>, Elapsed> = {
-
-                let task = async {
-                    
-                    self.send_request(SERVICE_ID, channel, &payload)?;
-
-                    loop {
-                        let results = match self.get_key_value(Some(key), Some(channel)) {
-                            Ok(ret) => ret,
-                            Err(_) => continue,
-                        };
-
-                        if let Some(results) = results {
-                            return Ok(results)
-                        }
-
-                        tokio::time::sleep(std::time::Duration::from_millis(CHECK_FIFO_POLLING_MS)).await;
-                    }
-                };
-        
-                tokio::time::timeout(timeout, task).await
-            };
+            let transfer_id: u8 = self.send_request_with_transfer_id(SERVICE_ID, channel, &payload)?;
+            let ret: Result<Result<TimestampedRxFrame, crate::Error>, Elapsed> = tokio::time::timeout(timeout, self.async_wait_response_on(&[VALUE_PORT_ID, RESULT_PORT_ID], channel, transfer_id)).await;
 
-            if let Ok(ret) = ret {
-                match ret {
-                    Ok(success) => return Ok(success),
-                    Err(e) => return Err(e)
+            if let Ok(frame) = ret {
+                if let Some(ret) = get_value_reply(channel, key, &frame?) {
+                    return ret.map(|data| vec![data]);
                 }
             }
         }
 
-        let err: std::io::Error = std::io::ErrorKind::TimedOut.into();
-        Err(err.into())
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: self.retry_count })
     }
 
-}
\ No newline at end of file
+}
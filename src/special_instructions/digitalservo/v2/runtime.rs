@@ -0,0 +1,66 @@
+use cands_presentation::cyphal::digitalservo::{
+    dictionary::{Dict, DigitalServoPrimitiveData, IntoDigitalServoDataType},
+    string::Str,
+};
+use crate::{TimestampedRxData, TimestampedRxFrame};
+
+use super::request_sync::{get_value_reply, set_value_reply};
+
+impl<B: crate::CANBackend> crate::CANRuntime<B> {
+
+    /// Same as `CANInterface::send_digitalservo_set_value`, but replies are matched by transfer ID,
+    /// so requests to several drives can be awaited concurrently.
+    pub async fn send_digitalservo_set_value<T>(
+        &self,
+        channel: u8,
+        key: &str,
+        value: &[T],
+    ) -> Result<(), crate::Error>
+    where
+        T: Clone + IntoDigitalServoDataType + Into<DigitalServoPrimitiveData>
+    {
+        const SERVICE_ID: u16 = 0x81;
+        let payload: Vec<u8> = Dict::serialize(key, value);
+
+        let (timeout, retry_count) = {
+            let interface = self.interface();
+            (interface.timeout, interface.retry_count)
+        };
+
+        for _ in 0..retry_count {
+            let frame: Option<TimestampedRxFrame> = self.request(SERVICE_ID, channel, &payload, timeout).await?;
+
+            if let Some(ret) = frame.and_then(|frame| set_value_reply(channel, key, &frame)) {
+                return ret;
+            }
+        }
+
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: retry_count })
+    }
+
+    /// Same as `CANInterface::send_digitalservo_get_value`, but replies are matched by transfer ID,
+    /// so requests to several drives can be awaited concurrently.
+    pub async fn send_digitalservo_get_value(
+        &self,
+        channel: u8,
+        key: &str,
+    ) -> Result<TimestampedRxData<Dict>, crate::Error> {
+        const SERVICE_ID: u16 = 0x82;
+        let payload: Vec<u8> = Str::serialize(key);
+
+        let (timeout, retry_count) = {
+            let interface = self.interface();
+            (interface.timeout, interface.retry_count)
+        };
+
+        for _ in 0..retry_count {
+            let frame: Option<TimestampedRxFrame> = self.request(SERVICE_ID, channel, &payload, timeout).await?;
+
+            if let Some(ret) = frame.and_then(|frame| get_value_reply(channel, key, &frame)) {
+                return ret;
+            }
+        }
+
+        Err(crate::Error::Timeout { channel, key: key.into(), attempts: retry_count })
+    }
+}
@@ -1,74 +1,48 @@
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use std::{thread, time};
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
 use cands_presentation::cyphal::digitalservo::dictionary::DigitalServoPrimitiveData;
 
-#[cfg(any(feature="usb-ftdi", feature="raspberrypi", feature="raspberrypi_cm"))]
-impl crate::CANInterface {
-    pub fn drive_enable(&mut self, channel: u8) -> Result<(), Box<dyn std::error::Error>> {
+use super::{CmdArray, CmdVal, Drive, CMDARRAY_LEN};
 
-        self.send_digitalservo_set_value(channel, "cmdval", &[0.0])?;
-        thread::sleep(time::Duration::from_millis(50));
-
-        self.send_digitalservo_set_value(channel, "cmdarray", &[0.0, 0.0, 0.0, 0.0])?;
-        thread::sleep(time::Duration::from_millis(50));
-
-        self.send_digitalservo_set_value(channel, "drive", &[true])?;
-        thread::sleep(time::Duration::from_millis(50));
-
-        Ok(())
-    }
-
-    pub fn drive_disable(&mut self, channel: u8) -> Result<(), Box<dyn std::error::Error>> {
-
-        self.send_digitalservo_set_value(channel, "drive", &[false])?;
-        thread::sleep(time::Duration::from_millis(100));
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Broadcast the enable sequence. The axes are not confirmed and their `DriveState` is left unchanged.
+    pub fn drive_enable_all(&mut self) -> Result<(), crate::Error> {
 
-        self.send_digitalservo_set_value(channel, "cmdval", &[0.0])?;
+        self.broadcast::<CmdVal>(0.0)?;
         thread::sleep(time::Duration::from_millis(50));
 
-        self.send_digitalservo_set_value(channel, "cmdarray", &[0.0, 0.0, 0.0, 0.0])?;
+        self.broadcast::<CmdArray>([0.0; CMDARRAY_LEN])?;
         thread::sleep(time::Duration::from_millis(50));
 
-        Ok(())
-    }
-
-
-    pub fn drive_enable_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-
-        self.send_digitalservo_message("cmdval", &[0.0])?;
-        thread::sleep(time::Duration::from_millis(50));
-
-        self.send_digitalservo_message("cmdarray", &[0.0, 0.0, 0.0, 0.0])?;
-        thread::sleep(time::Duration::from_millis(50));
-
-        self.send_digitalservo_message("drive", &[true])?;
+        self.broadcast::<Drive>(true)?;
         thread::sleep(time::Duration::from_millis(50));
 
         Ok(())
     }
 
-    pub fn drive_disable_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Broadcast the disable sequence. The axes are not confirmed and their `DriveState` is left unchanged.
+    pub fn drive_disable_all(&mut self) -> Result<(), crate::Error> {
 
-        self.send_digitalservo_message("drive", &[false])?;
+        self.broadcast::<Drive>(false)?;
         thread::sleep(time::Duration::from_millis(50));
 
-        self.send_digitalservo_message("cmdval", &[0.0])?;
+        self.broadcast::<CmdVal>(0.0)?;
         thread::sleep(time::Duration::from_millis(50));
 
-        self.send_digitalservo_message("cmdarray", &[0.0, 0.0, 0.0, 0.0])?;
+        self.broadcast::<CmdArray>([0.0; CMDARRAY_LEN])?;
         thread::sleep(time::Duration::from_millis(50));
 
         Ok(())
     }
 
-    pub fn send_cmdval(&mut self, channel: u8, value: f64) -> Result<(), Box<dyn std::error::Error>> {
+    /// Write `cmdval` without checking the `DriveState` of the axis; `set::<CmdVal>` checks it.
+    pub fn send_cmdval(&mut self, channel: u8, value: f64) -> Result<(), crate::Error> {
         self.send_digitalservo_set_value(channel, "cmdval", &[value])
     }
 
-    pub fn send_cmdarray(&mut self, channel: u8, value: &[f64]) -> Result<(), Box<dyn std::error::Error>> {
+    /// Write `cmdarray` without checking the `DriveState` of the axis; `set::<CmdArray>` checks it.
+    pub fn send_cmdarray(&mut self, channel: u8, value: &[f64]) -> Result<(), crate::Error> {
         self.send_digitalservo_set_value(channel, "cmdarray", value)
     }
 
@@ -80,31 +54,28 @@ impl crate::CANInterface {
             Err(_) => return None,
         };
 
-        match ret.last() {
-            Some(data) => Some(data.data.value.clone()),
-            None => return None,
-        }
+        ret.last().map(|data| data.data.value.clone())
     }
 
-    pub fn get_scalar_response<T: TryFrom<DigitalServoPrimitiveData>>(&mut self, channel: u8, key: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    pub fn get_scalar_response<T: TryFrom<DigitalServoPrimitiveData>>(&mut self, channel: u8, key: &str) -> Result<Option<T>, crate::Error> {
 
         let res = match self.get_vector_response(channel, key) {
             Some(data) => data,
             None => return Ok(None)
         };
 
-        let data = match res.get(0) {
+        let data = match res.first() {
             Some(data) => data,
             None => return Ok(None)
         };
 
         match T::try_from(data.clone()) {
             Ok(ret) => Ok(Some(ret)),
-            Err(_e) => Err("Type Not Mismatch".into())
+            Err(_e) => Err(crate::Error::TypeConversion { key: key.into() })
         }
     }
 
-    pub fn get_scalar_response_from_buffer<T: TryFrom<DigitalServoPrimitiveData>>(&mut self, channel: u8, key: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    pub fn get_scalar_response_from_buffer<T: TryFrom<DigitalServoPrimitiveData>>(&mut self, channel: u8, key: &str) -> Result<Option<T>, crate::Error> {
         let data = match self.get_key_value(Some(key), Some(channel))? {
             Some(data) => data,
             None => return Ok(None)
@@ -115,14 +86,14 @@ impl crate::CANInterface {
             None => return Ok(None)
         };
 
-        let scalar = match last_data.get(0) {
+        let scalar = match last_data.first() {
             Some(data) => data.clone(),
             None => return Ok(None)
         };
 
         match T::try_from(scalar) {
             Ok(data) => Ok(Some(data)),
-            Err(_) => Err("Type Not Mismatch".into())
+            Err(_) => Err(crate::Error::TypeConversion { key: key.into() })
         }
 
     }
@@ -1 +1,2 @@
-pub mod digitalservo;
\ No newline at end of file
+pub mod digitalservo;
+pub mod uavcan;
@@ -0,0 +1,170 @@
+use std::time::Instant;
+
+use cands_transport::cyphal::CyphalTransferKind;
+#[cfg(feature="log")]
+use tokio::task::JoinHandle;
+
+use super::dsdl::{Reader, Writer};
+use super::synchronized_time;
+use crate::{Subscription, TimestampedRxData, TimestampedRxFrame};
+
+/// Subject ID of `uavcan.diagnostic.Record.1.1`.
+pub const DIAGNOSTIC_RECORD_SUBJECT_ID: u16 = 8184;
+
+const TEXT_MAX_LEN: usize = 255;
+
+/// `uavcan.diagnostic.Severity.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Trace = 0,
+    Debug = 1,
+    Info = 2,
+    Notice = 3,
+    Warning = 4,
+    Error = 5,
+    Critical = 6,
+    Alert = 7,
+}
+
+impl From<u8> for Severity {
+    fn from(x: u8) -> Self {
+        match x & 0x07 {
+            0 => Severity::Trace,
+            1 => Severity::Debug,
+            2 => Severity::Info,
+            3 => Severity::Notice,
+            4 => Severity::Warning,
+            5 => Severity::Error,
+            6 => Severity::Critical,
+            _ => Severity::Alert,
+        }
+    }
+}
+
+#[cfg(feature="log")]
+impl From<Severity> for log::Level {
+    fn from(x: Severity) -> Self {
+        match x {
+            Severity::Trace => log::Level::Trace,
+            Severity::Debug => log::Level::Debug,
+            Severity::Info | Severity::Notice => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error | Severity::Critical | Severity::Alert => log::Level::Error,
+        }
+    }
+}
+
+/// `uavcan.diagnostic.Record.1.1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticRecord {
+    /// Synchronized time in microseconds, 0 if unknown.
+    pub timestamp: u64,
+    pub severity: Severity,
+    pub text: String,
+}
+
+impl DiagnosticRecord {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer
+            .bytes(&self.timestamp.to_le_bytes()[..7])
+            .u8(self.severity as u8)
+            .array_u8(self.text.as_bytes(), TEXT_MAX_LEN);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let timestamp: [u8; 7] = reader.bytes();
+        let mut timestamp_bytes: [u8; 8] = [0; 8];
+        timestamp_bytes[..7].copy_from_slice(&timestamp);
+        let severity: Severity = reader.u8().into();
+        let text: String = String::from_utf8_lossy(&reader.array_u8(TEXT_MAX_LEN)?).into_owned();
+
+        Ok(Self { timestamp: u64::from_le_bytes(timestamp_bytes), severity, text })
+    }
+
+    /// Forward to the `log` facade, tagged with the source node ID. `tracing` picks it up through `tracing-log`.
+    #[cfg(feature="log")]
+    pub fn log(&self, source_node_id: u8) {
+        log::log!(target: "cands_cyphal::diagnostic", self.severity.into(), "node {}: {}", source_node_id, self.text);
+    }
+}
+
+fn is_diagnostic_record(frame: &TimestampedRxFrame) -> bool {
+    (frame.props.transfer_kind == CyphalTransferKind::Message) & (frame.props.port_id == DIAGNOSTIC_RECORD_SUBJECT_ID)
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Publish a diagnostic record stamped with the current `synchronized_time`. Text beyond 255 bytes is cut off.
+    pub fn publish_diagnostic(&mut self, severity: Severity, text: &str) -> Result<(), crate::Error> {
+        let record: DiagnosticRecord = DiagnosticRecord { timestamp: synchronized_time(Instant::now()), severity, text: text.to_string() };
+        self.send_message(DIAGNOSTIC_RECORD_SUBJECT_ID, &record.serialize())
+    }
+
+    /// Take received diagnostic records, optionally only those of one node.
+    pub fn get_diagnostic(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<TimestampedRxData<DiagnosticRecord>>>, crate::Error> {
+        let mut buffer: Vec<TimestampedRxData<DiagnosticRecord>> = Vec::new();
+
+        // Load data from a device FIFO and put RxFrames on a user-space FIFO
+        self.load_frames()?;
+
+        self.rx_complete_fifo.retain(|packet| {
+            if !is_diagnostic_record(packet) {
+                return true;
+            }
+            if source_node_id.is_some_and(|node_id| packet.props.source_node_id != node_id) {
+                return true;
+            }
+            if let Ok(data) = DiagnosticRecord::deserialize(&packet.payload[..packet.payload_size]) {
+                buffer.push(TimestampedRxData { data, props: packet.props, timestamp: packet.timestamp });
+            }
+            false
+        });
+
+        match buffer.len() {
+            0 => Ok(None),
+            _ => Ok(Some(buffer))
+        }
+    }
+}
+
+impl<B: crate::CANBackend + Send + 'static> crate::CANRuntime<B> {
+    /// Receive diagnostic records, optionally only those of one node.
+    pub fn subscribe_diagnostic(&self, source_node_id: Option<u8>) -> Subscription<TimestampedRxData<DiagnosticRecord>> {
+        self.subscribe_with(move |frame| {
+            if !is_diagnostic_record(frame) || source_node_id.is_some_and(|node_id| frame.props.source_node_id != node_id) {
+                return None;
+            }
+            let data: DiagnosticRecord = DiagnosticRecord::deserialize(&frame.payload[..frame.payload_size]).ok()?;
+            Some(TimestampedRxData { data, props: frame.props, timestamp: frame.timestamp })
+        })
+    }
+}
+
+/// Forwards every diagnostic record received by a `CANRuntime` to the `log` facade.
+#[cfg(feature="log")]
+pub struct DiagnosticLogger {
+    task: JoinHandle<()>,
+}
+
+#[cfg(feature="log")]
+impl DiagnosticLogger {
+    /// Must be called from within a tokio runtime.
+    pub fn spawn<B: crate::CANBackend + Send + 'static>(runtime: &crate::CANRuntime<B>) -> Self {
+        let mut records = runtime.subscribe_diagnostic(None);
+        let task: JoinHandle<()> = tokio::spawn(async move {
+            while let Ok(record) = records.recv().await {
+                record.data.log(record.props.source_node_id);
+            }
+        });
+        Self { task }
+    }
+}
+
+#[cfg(feature="log")]
+impl Drop for DiagnosticLogger {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,142 @@
+//! Little-endian DSDL encoding helpers for the standard `uavcan` types.
+
+/// Reads fields in order. Reading past the end yields zeros (DSDL implicit zero extension).
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub(crate) fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut ret: [u8; N] = [0; N];
+        for byte in ret.iter_mut() {
+            *byte = self.bytes.get(self.position).copied().unwrap_or(0);
+            self.position += 1;
+        }
+        ret
+    }
+
+    pub(crate) fn u8(&mut self) -> u8 {
+        u8::from_le_bytes(self.bytes())
+    }
+
+    pub(crate) fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.bytes())
+    }
+
+    pub(crate) fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.bytes())
+    }
+
+    /// The length prefix of a variable-length array: 8 bits up to a capacity of 255, 16 bits above.
+    pub(crate) fn len(&mut self, max_len: usize) -> Result<usize, crate::Error> {
+        let len: usize = match max_len <= u8::MAX as usize {
+            true => self.u8() as usize,
+            false => self.u16() as usize
+        };
+        self.check_len(len, max_len)?;
+        Ok(len)
+    }
+
+    /// A variable-length byte array.
+    pub(crate) fn array_u8(&mut self, max_len: usize) -> Result<Vec<u8>, crate::Error> {
+        let len: usize = self.len(max_len)?;
+        Ok((0..len).map(|_| self.u8()).collect())
+    }
+
+    pub(crate) fn check_len(&self, len: usize, max_len: usize) -> Result<(), crate::Error> {
+        match len <= max_len {
+            true => Ok(()),
+            false => Err(crate::Error::Serialization(format!("array length {} exceeds {}", len, max_len)))
+        }
+    }
+}
+
+/// Appends fields in order.
+#[derive(Default)]
+pub(crate) struct Writer {
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn u8(&mut self, x: u8) -> &mut Self {
+        self.bytes.push(x);
+        self
+    }
+
+    pub(crate) fn u16(&mut self, x: u16) -> &mut Self {
+        self.bytes.extend(x.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn u64(&mut self, x: u64) -> &mut Self {
+        self.bytes.extend(x.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn bytes(&mut self, x: &[u8]) -> &mut Self {
+        self.bytes.extend(x);
+        self
+    }
+
+    /// The length prefix of a variable-length array, sized by its capacity like `Reader::len`.
+    pub(crate) fn len(&mut self, len: usize, max_len: usize) -> &mut Self {
+        match max_len <= u8::MAX as usize {
+            true => self.u8(len as u8),
+            false => self.u16(len as u16)
+        }
+    }
+
+    /// A variable-length byte array, truncated to `max_len`.
+    pub(crate) fn array_u8(&mut self, x: &[u8], max_len: usize) -> &mut Self {
+        let x: &[u8] = &x[..x.len().min(max_len)];
+        self.len(x.len(), max_len).bytes(x)
+    }
+}
+
+/// IEEE 754 binary16 to `f32`.
+pub(crate) fn f16_to_f32(x: u16) -> f32 {
+    let sign: u32 = ((x & 0x8000) as u32) << 16;
+    let exponent: u32 = ((x >> 10) & 0x1F) as u32;
+    let mantissa: u32 = (x & 0x3FF) as u32;
+    match exponent {
+        0 => {
+            let magnitude: f32 = mantissa as f32 * f32::powi(2.0, -24);
+            f32::from_bits(sign | magnitude.to_bits())
+        },
+        0x1F => f32::from_bits(sign | 0x7F80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13))
+    }
+}
+
+/// `f32` to IEEE 754 binary16, rounding to nearest. Out-of-range values saturate to infinity.
+pub(crate) fn f32_to_f16(x: f32) -> u16 {
+    let bits: u32 = x.to_bits();
+    let sign: u16 = ((bits >> 16) & 0x8000) as u16;
+    let exponent: i32 = ((bits >> 23) & 0xFF) as i32;
+    let mantissa: u32 = bits & 0x7F_FFFF;
+
+    if exponent == 0xFF {
+        return sign | 0x7C00 | match mantissa { 0 => 0, _ => 0x200 };
+    }
+    let exponent: i32 = exponent - 127 + 15;
+    if exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa: u32 = mantissa | 0x80_0000;
+        let shift: u32 = (14 - exponent) as u32;
+        let round: u32 = (mantissa >> (shift - 1)) & 1;
+        return sign | ((mantissa >> shift) + round) as u16;
+    }
+    // A carry out of the mantissa correctly bumps the exponent.
+    let round: u16 = ((mantissa >> 12) & 1) as u16;
+    (sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16) + round
+}
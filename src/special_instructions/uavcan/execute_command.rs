@@ -0,0 +1,156 @@
+use super::dsdl::{Reader, Writer};
+
+/// Service ID of `uavcan.node.ExecuteCommand.1.1`.
+pub const EXECUTE_COMMAND_SERVICE_ID: u16 = 435;
+
+const PARAMETER_MAX_LEN: usize = 255;
+
+/// Command of `uavcan.node.ExecuteCommand.1.1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Restart,
+    PowerOff,
+    /// The parameter is the path of the image on the requesting node's file server.
+    BeginSoftwareUpdate,
+    FactoryReset,
+    EmergencyStop,
+    StorePersistentStates,
+    /// Vendor-specific command.
+    Other(u16),
+}
+
+impl From<u16> for Command {
+    fn from(x: u16) -> Self {
+        match x {
+            65535 => Command::Restart,
+            65534 => Command::PowerOff,
+            65533 => Command::BeginSoftwareUpdate,
+            65532 => Command::FactoryReset,
+            65531 => Command::EmergencyStop,
+            65530 => Command::StorePersistentStates,
+            x => Command::Other(x)
+        }
+    }
+}
+
+impl From<Command> for u16 {
+    fn from(x: Command) -> Self {
+        match x {
+            Command::Restart => 65535,
+            Command::PowerOff => 65534,
+            Command::BeginSoftwareUpdate => 65533,
+            Command::FactoryReset => 65532,
+            Command::EmergencyStop => 65531,
+            Command::StorePersistentStates => 65530,
+            Command::Other(x) => x
+        }
+    }
+}
+
+/// Response status of `uavcan.node.ExecuteCommand.1.1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    Success,
+    Failure,
+    NotAuthorized,
+    BadCommand,
+    BadParameter,
+    BadState,
+    InternalError,
+    Other(u8),
+}
+
+impl From<u8> for CommandStatus {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => CommandStatus::Success,
+            1 => CommandStatus::Failure,
+            2 => CommandStatus::NotAuthorized,
+            3 => CommandStatus::BadCommand,
+            4 => CommandStatus::BadParameter,
+            5 => CommandStatus::BadState,
+            6 => CommandStatus::InternalError,
+            x => CommandStatus::Other(x)
+        }
+    }
+}
+
+impl From<CommandStatus> for u8 {
+    fn from(x: CommandStatus) -> Self {
+        match x {
+            CommandStatus::Success => 0,
+            CommandStatus::Failure => 1,
+            CommandStatus::NotAuthorized => 2,
+            CommandStatus::BadCommand => 3,
+            CommandStatus::BadParameter => 4,
+            CommandStatus::BadState => 5,
+            CommandStatus::InternalError => 6,
+            CommandStatus::Other(x) => x
+        }
+    }
+}
+
+/// Request of `uavcan.node.ExecuteCommand.1.1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecuteCommandRequest {
+    pub command: Command,
+    pub parameter: Vec<u8>,
+}
+
+impl ExecuteCommandRequest {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer
+            .u16(self.command.into())
+            .array_u8(&self.parameter, PARAMETER_MAX_LEN);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let command: Command = reader.u16().into();
+        let parameter: Vec<u8> = reader.array_u8(PARAMETER_MAX_LEN)?;
+        Ok(Self { command, parameter })
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Send `command` to `channel` and return the status it answers with.
+    ///
+    /// Each attempt waits Self::timeout for the response, up to Self::retry_count attempts.
+    pub fn execute_command(&mut self, channel: u8, command: Command, parameter: &[u8]) -> Result<CommandStatus, crate::Error> {
+        let request: ExecuteCommandRequest = ExecuteCommandRequest { command, parameter: parameter.to_vec() };
+        let payload: Vec<u8> = request.serialize();
+
+        for _ in 0..self.retry_count {
+            let transfer_id: u8 = self.send_request_with_transfer_id(EXECUTE_COMMAND_SERVICE_ID, channel, &payload)?;
+
+            if let Some(frame) = self.wait_response(EXECUTE_COMMAND_SERVICE_ID, channel, transfer_id, self.timeout)? {
+                return Ok(Reader::new(&frame.payload[..frame.payload_size]).u8().into());
+            }
+        }
+
+        Err(crate::Error::Timeout { channel, key: format!("{:?}", command), attempts: self.retry_count })
+    }
+
+    pub fn restart(&mut self, channel: u8) -> Result<CommandStatus, crate::Error> {
+        self.execute_command(channel, Command::Restart, &[])
+    }
+
+    pub fn power_off(&mut self, channel: u8) -> Result<CommandStatus, crate::Error> {
+        self.execute_command(channel, Command::PowerOff, &[])
+    }
+
+    pub fn factory_reset(&mut self, channel: u8) -> Result<CommandStatus, crate::Error> {
+        self.execute_command(channel, Command::FactoryReset, &[])
+    }
+
+    pub fn store_persistent_states(&mut self, channel: u8) -> Result<CommandStatus, crate::Error> {
+        self.execute_command(channel, Command::StorePersistentStates, &[])
+    }
+
+    /// Ask `channel` to fetch the image at `path` from this node's file server and install it.
+    pub fn begin_software_update(&mut self, channel: u8, path: &str) -> Result<CommandStatus, crate::Error> {
+        self.execute_command(channel, Command::BeginSoftwareUpdate, path.as_bytes())
+    }
+}
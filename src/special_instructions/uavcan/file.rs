@@ -0,0 +1,374 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, Weak};
+use std::time::{Instant, UNIX_EPOCH};
+
+use cands_transport::cyphal::CyphalTransferKind;
+use tokio::task::JoinHandle;
+
+use super::dsdl::{Reader, Writer};
+use crate::TimestampedRxFrame;
+
+/// Service ID of `uavcan.file.GetInfo.0.2`.
+pub const FILE_GET_INFO_SERVICE_ID: u16 = 405;
+/// Service ID of `uavcan.file.Read.1.1`.
+pub const FILE_READ_SERVICE_ID: u16 = 408;
+
+const PATH_MAX_LEN: usize = 255;
+/// A shorter `Read` response marks the end of the file.
+pub const FILE_READ_CHUNK_SIZE: usize = 256;
+
+/// `uavcan.file.Error.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileError {
+    Ok,
+    NotFound,
+    IoError,
+    AccessDenied,
+    IsDirectory,
+    InvalidValue,
+    FileTooLarge,
+    OutOfSpace,
+    NotSupported,
+    Unknown(u16),
+}
+
+impl From<u16> for FileError {
+    fn from(x: u16) -> Self {
+        match x {
+            0 => FileError::Ok,
+            2 => FileError::NotFound,
+            5 => FileError::IoError,
+            13 => FileError::AccessDenied,
+            21 => FileError::IsDirectory,
+            22 => FileError::InvalidValue,
+            27 => FileError::FileTooLarge,
+            28 => FileError::OutOfSpace,
+            38 => FileError::NotSupported,
+            x => FileError::Unknown(x)
+        }
+    }
+}
+
+impl From<FileError> for u16 {
+    fn from(x: FileError) -> Self {
+        match x {
+            FileError::Ok => 0,
+            FileError::NotFound => 2,
+            FileError::IoError => 5,
+            FileError::AccessDenied => 13,
+            FileError::IsDirectory => 21,
+            FileError::InvalidValue => 22,
+            FileError::FileTooLarge => 27,
+            FileError::OutOfSpace => 28,
+            FileError::NotSupported => 38,
+            FileError::Unknown(x) => x
+        }
+    }
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => FileError::NotFound,
+            std::io::ErrorKind::PermissionDenied => FileError::AccessDenied,
+            _ => FileError::IoError
+        }
+    }
+}
+
+/// Request of `uavcan.file.Read.1.1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReadRequest {
+    pub offset: u64,
+    pub path: String,
+}
+
+impl FileReadRequest {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer
+            .bytes(&self.offset.to_le_bytes()[..5])
+            .array_u8(self.path.as_bytes(), PATH_MAX_LEN);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let offset: [u8; 5] = reader.bytes();
+        let mut offset_bytes: [u8; 8] = [0; 8];
+        offset_bytes[..5].copy_from_slice(&offset);
+        let path: String = String::from_utf8_lossy(&reader.array_u8(PATH_MAX_LEN)?).into_owned();
+        Ok(Self { offset: u64::from_le_bytes(offset_bytes), path })
+    }
+}
+
+/// Response of `uavcan.file.Read.1.1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReadResponse {
+    pub error: FileError,
+    pub data: Vec<u8>,
+}
+
+impl FileReadResponse {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer
+            .u16(self.error.into())
+            .array_u8(&self.data, FILE_READ_CHUNK_SIZE);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let error: FileError = reader.u16().into();
+        let data: Vec<u8> = reader.array_u8(FILE_READ_CHUNK_SIZE)?;
+        Ok(Self { error, data })
+    }
+}
+
+/// Response of `uavcan.file.GetInfo.0.2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    pub error: FileError,
+    pub size: u64,
+    pub unix_timestamp_of_last_modification: u64,
+    pub is_file_not_directory: bool,
+    pub is_link: bool,
+    pub is_readable: bool,
+    pub is_writeable: bool,
+}
+
+impl FileInfo {
+    fn error(error: FileError) -> Self {
+        Self {
+            error,
+            size: 0,
+            unix_timestamp_of_last_modification: 0,
+            is_file_not_directory: false,
+            is_link: false,
+            is_readable: false,
+            is_writeable: false,
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let flags: u8 = (self.is_file_not_directory as u8)
+            | ((self.is_link as u8) << 1)
+            | ((self.is_readable as u8) << 2)
+            | ((self.is_writeable as u8) << 3);
+        let mut writer: Writer = Writer::default();
+        writer
+            .u16(self.error.into())
+            .bytes(&self.size.to_le_bytes()[..5])
+            .bytes(&self.unix_timestamp_of_last_modification.to_le_bytes()[..5])
+            .u8(flags);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let error: FileError = reader.u16().into();
+        let mut uint40 = || {
+            let bytes: [u8; 5] = reader.bytes();
+            let mut ret: [u8; 8] = [0; 8];
+            ret[..5].copy_from_slice(&bytes);
+            u64::from_le_bytes(ret)
+        };
+        let (size, unix_timestamp_of_last_modification) = (uint40(), uint40());
+        let flags: u8 = reader.u8();
+
+        Ok(Self {
+            error,
+            size,
+            unix_timestamp_of_last_modification,
+            is_file_not_directory: flags & 0x01 != 0,
+            is_link: flags & 0x02 != 0,
+            is_readable: flags & 0x04 != 0,
+            is_writeable: flags & 0x08 != 0,
+        })
+    }
+}
+
+/// A file read served to a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReadEvent {
+    pub node_id: u8,
+    pub path: String,
+    pub offset: u64,
+    pub len: usize,
+    /// Receive time of the read request.
+    pub timestamp: Instant,
+}
+
+/// Read-only `uavcan.file` server for the files under `root`. Paths leaving `root`, also through symlinks, are denied.
+#[derive(Debug, Clone)]
+pub struct FileServer {
+    root: PathBuf,
+    only: Option<PathBuf>,
+}
+
+impl FileServer {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self { root: root.as_ref().to_path_buf(), only: None }
+    }
+
+    /// Serve the file at `path` under its file name and deny every other path.
+    pub fn file(path: impl AsRef<Path>) -> Self {
+        let path: &Path = path.as_ref();
+        Self {
+            root: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            only: Some(path.file_name().map_or_else(PathBuf::new, PathBuf::from)),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Canonical path of `path` under `root`.
+    fn resolve(&self, path: &str) -> Result<PathBuf, FileError> {
+        let path: PathBuf = Path::new(path).components().filter(|component| *component != Component::CurDir).collect();
+        if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(FileError::AccessDenied);
+        }
+        if self.only.as_ref().is_some_and(|only| *only != path) {
+            return Err(FileError::AccessDenied);
+        }
+
+        let root: PathBuf = self.root.canonicalize()?;
+        let resolved: PathBuf = self.root.join(path).canonicalize()?;
+        match resolved.starts_with(&root) {
+            true => Ok(resolved),
+            false => Err(FileError::AccessDenied)
+        }
+    }
+
+    pub fn read(&self, path: &str, offset: u64) -> FileReadResponse {
+        let read = || -> Result<Vec<u8>, FileError> {
+            let path: PathBuf = self.resolve(path)?;
+            if path.is_dir() {
+                return Err(FileError::IsDirectory);
+            }
+            let mut file: std::fs::File = std::fs::File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut data: Vec<u8> = Vec::with_capacity(FILE_READ_CHUNK_SIZE);
+            file.take(FILE_READ_CHUNK_SIZE as u64).read_to_end(&mut data)?;
+            Ok(data)
+        };
+
+        match read() {
+            Ok(data) => FileReadResponse { error: FileError::Ok, data },
+            Err(error) => FileReadResponse { error, data: vec![] }
+        }
+    }
+
+    pub fn get_info(&self, path: &str) -> FileInfo {
+        let get_info = || -> Result<FileInfo, FileError> {
+            let resolved: PathBuf = self.resolve(path)?;
+            let is_link: bool = std::fs::symlink_metadata(self.root.join(path))?.file_type().is_symlink();
+            let metadata: std::fs::Metadata = std::fs::metadata(&resolved)?;
+            let modified: u64 = metadata.modified().ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |modified| modified.as_secs());
+
+            Ok(FileInfo {
+                error: FileError::Ok,
+                size: metadata.len(),
+                unix_timestamp_of_last_modification: modified,
+                is_file_not_directory: metadata.is_file(),
+                is_link,
+                is_readable: true,
+                is_writeable: false,
+            })
+        };
+        get_info().unwrap_or_else(FileInfo::error)
+    }
+
+    /// Answer a `Read` or `GetInfo` request. Returns the response payload, or `None` for other or malformed transfers.
+    fn handle_request(&self, frame: &TimestampedRxFrame) -> Option<(Vec<u8>, Option<FileReadEvent>)> {
+        let payload: &[u8] = &frame.payload[..frame.payload_size];
+
+        match frame.props.port_id {
+            FILE_READ_SERVICE_ID => {
+                let request: FileReadRequest = FileReadRequest::deserialize(payload).ok()?;
+                let response: FileReadResponse = self.read(&request.path, request.offset);
+                let event: Option<FileReadEvent> = (response.error == FileError::Ok).then(|| FileReadEvent {
+                    node_id: frame.props.source_node_id,
+                    path: request.path,
+                    offset: request.offset,
+                    len: response.data.len(),
+                    timestamp: frame.timestamp,
+                });
+                Some((response.serialize(), event))
+            },
+            FILE_GET_INFO_SERVICE_ID => {
+                let path: Vec<u8> = Reader::new(payload).array_u8(PATH_MAX_LEN).ok()?;
+                Some((self.get_info(&String::from_utf8_lossy(&path)).serialize(), None))
+            },
+            _ => None
+        }
+    }
+
+    /// Answer the file requests received by `interface`. Returns the reads served.
+    pub fn serve<B: crate::CANBackend>(&self, interface: &mut crate::CANInterface<B>) -> Result<Vec<FileReadEvent>, crate::Error> {
+        interface.load_frames()?;
+        let node_id: u8 = interface.node_id;
+
+        let mut requests: Vec<TimestampedRxFrame> = vec![];
+        interface.rx_complete_fifo.retain(|frame| {
+            if !is_file_request(frame, node_id) {
+                return true;
+            }
+            requests.push(frame.clone());
+            false
+        });
+
+        let mut ret: Vec<FileReadEvent> = vec![];
+        for frame in requests {
+            // A malformed request gets no answer, like on any other Cyphal server.
+            if let Some((response, event)) = self.handle_request(&frame) {
+                interface.send_response_with_transfer_id(frame.props.port_id, frame.props.source_node_id, frame.props.transfer_id, &response)?;
+                ret.extend(event);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Serve file requests received by `runtime` from a background task. Must be called from within a tokio runtime.
+    pub fn spawn<B: crate::CANBackend + Send + 'static>(self, runtime: &crate::CANRuntime<B>) -> FileServerTask {
+        let node_id: u8 = runtime.interface().node_id;
+        let interface: Weak<Mutex<crate::CANInterface<B>>> = runtime.weak_interface();
+        let mut requests = runtime.subscribe_with(move |frame| is_file_request(frame, node_id).then(|| frame.clone()));
+
+        let task: JoinHandle<()> = tokio::spawn(async move {
+            while let Ok(frame) = requests.recv().await {
+                if let Some((response, _)) = self.handle_request(&frame) {
+                    // The runtime is stopping once the interface is gone.
+                    let Some(interface) = interface.upgrade() else { break };
+                    let mut interface = interface.lock().unwrap_or_else(|err| err.into_inner());
+                    let _ = interface.send_response_with_transfer_id(frame.props.port_id, frame.props.source_node_id, frame.props.transfer_id, &response);
+                }
+            }
+        });
+
+        FileServerTask { task }
+    }
+}
+
+fn is_file_request(frame: &TimestampedRxFrame, node_id: u8) -> bool {
+    (frame.props.transfer_kind == CyphalTransferKind::Request)
+        & [FILE_READ_SERVICE_ID, FILE_GET_INFO_SERVICE_ID].contains(&frame.props.port_id)
+        & (frame.props.destination_node_id == node_id)
+}
+
+/// A `FileServer` running on a `CANRuntime`; stops when dropped.
+pub struct FileServerTask {
+    task: JoinHandle<()>,
+}
+
+impl Drop for FileServerTask {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
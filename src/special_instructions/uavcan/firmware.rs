@@ -0,0 +1,97 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{CommandStatus, FileServer, Health, Mode};
+
+/// Default longest pause in a firmware update: no file read and no `SoftwareUpdate` heartbeat for this long fails it.
+pub const FIRMWARE_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// State of a running firmware update, passed to the progress callback whenever it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateProgress {
+    /// End of the furthest chunk of the image the node has read.
+    pub bytes_read: u64,
+    pub file_size: u64,
+    /// Mode from the node's latest heartbeat.
+    pub mode: Option<Mode>,
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Flash the image at `path` to `channel` and wait until the node leaves software update mode.
+    pub fn update_firmware(&mut self, channel: u8, path: impl AsRef<Path>) -> Result<(), crate::Error> {
+        self.update_firmware_with_progress(channel, path, FIRMWARE_UPDATE_TIMEOUT, |_| {})
+    }
+
+    /// Serve `path` over `uavcan.file`, send `BeginSoftwareUpdate` to `channel` and track the update through
+    /// the file reads and the node's heartbeat mode. The update is done at the first heartbeat out of
+    /// `SoftwareUpdate` mode received after the last file read. Fails if the node makes no progress for
+    /// `silence_timeout`, leaves `SoftwareUpdate` without reading the image or comes back with `Health::Warning`.
+    pub fn update_firmware_with_progress(
+        &mut self,
+        channel: u8,
+        path: impl AsRef<Path>,
+        silence_timeout: Duration,
+        mut progress: impl FnMut(&UpdateProgress)
+    ) -> Result<(), crate::Error> {
+        let path: &Path = path.as_ref();
+        let name: String = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => return Err(crate::Error::InvalidConfig(format!("\"{}\" is not a file", path.display())))
+        };
+        let file_size: u64 = std::fs::metadata(path).map_err(crate::Error::Storage)?.len();
+        let server: FileServer = FileServer::file(path);
+
+        let failed = |reason: String| crate::Error::FirmwareUpdate { channel, reason };
+
+        match self.begin_software_update(channel, &name)? {
+            CommandStatus::Success => {},
+            status => return Err(failed(format!("BeginSoftwareUpdate answered {:?}", status)))
+        }
+
+        let mut state: UpdateProgress = UpdateProgress { bytes_read: 0, file_size, mode: None };
+        let mut updating: bool = false;
+        let mut last_read: Option<Instant> = None;
+        let mut last_progress: Instant = Instant::now();
+
+        loop {
+            for event in server.serve(self)? {
+                if (event.node_id == channel) & (event.path == name) {
+                    state.bytes_read = state.bytes_read.max(event.offset + event.len as u64);
+                    last_read = Some(last_read.map_or(event.timestamp, |last_read| last_read.max(event.timestamp)));
+                    last_progress = Instant::now();
+                    progress(&state);
+                }
+            }
+
+            for heartbeat in self.get_heartbeat(Some(channel))?.unwrap_or_default() {
+                if state.mode != Some(heartbeat.data.mode) {
+                    state.mode = Some(heartbeat.data.mode);
+                    progress(&state);
+                }
+                match heartbeat.data.mode {
+                    Mode::SoftwareUpdate => {
+                        updating = true;
+                        last_progress = Instant::now();
+                    },
+                    _ if updating => match last_read {
+                        None => return Err(failed("node left software update mode without reading the image".into())),
+                        // Sent before the last read, so the node has not finished yet.
+                        Some(last_read) if heartbeat.timestamp <= last_read => {},
+                        Some(_) => {
+                            return match heartbeat.data.health {
+                                Health::Warning => Err(failed("node reports Warning health after the update".into())),
+                                _ => Ok(())
+                            };
+                        }
+                    },
+                    _ => {}
+                }
+            }
+
+            if last_progress.elapsed() > silence_timeout {
+                return Err(crate::Error::Timeout { channel, key: "software update".into(), attempts: 1 });
+            }
+            std::thread::sleep(crate::RESPONSE_POLLING_INTERVAL);
+        }
+    }
+}
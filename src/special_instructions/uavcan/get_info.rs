@@ -0,0 +1,129 @@
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use cands_transport::cyphal::CYPHAL_NODE_ID_MAX;
+
+use super::dsdl::{Reader, Writer};
+use crate::TimestampedRxFrame;
+
+/// Service ID of `uavcan.node.GetInfo.1.0`.
+pub const GET_INFO_SERVICE_ID: u16 = 430;
+
+const NAME_MAX_LEN: usize = 50;
+const CERTIFICATE_MAX_LEN: usize = 222;
+
+/// `uavcan.node.Version.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+/// Response of `uavcan.node.GetInfo.1.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeInfo {
+    pub protocol_version: Version,
+    pub hardware_version: Version,
+    pub software_version: Version,
+    pub software_vcs_revision_id: u64,
+    pub unique_id: [u8; 16],
+    pub name: String,
+    pub software_image_crc: Option<u64>,
+    pub certificate_of_authenticity: Vec<u8>,
+}
+
+impl NodeInfo {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        for version in [self.protocol_version, self.hardware_version, self.software_version] {
+            writer.u8(version.major).u8(version.minor);
+        }
+        writer
+            .u64(self.software_vcs_revision_id)
+            .bytes(&self.unique_id)
+            .array_u8(self.name.as_bytes(), NAME_MAX_LEN);
+        match self.software_image_crc {
+            Some(crc) => writer.u8(1).u64(crc),
+            None => writer.u8(0),
+        };
+        writer.array_u8(&self.certificate_of_authenticity, CERTIFICATE_MAX_LEN);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let mut version = || Version { major: reader.u8(), minor: reader.u8() };
+        let (protocol_version, hardware_version, software_version) = (version(), version(), version());
+
+        let software_vcs_revision_id: u64 = reader.u64();
+        let unique_id: [u8; 16] = reader.bytes();
+        let name: String = String::from_utf8_lossy(&reader.array_u8(NAME_MAX_LEN)?).into_owned();
+        let software_image_crc: Option<u64> = match reader.u8() {
+            0 => None,
+            1 => Some(reader.u64()),
+            len => return Err(crate::Error::Serialization(format!("array length {} exceeds 1", len)))
+        };
+        let certificate_of_authenticity: Vec<u8> = reader.array_u8(CERTIFICATE_MAX_LEN)?;
+
+        Ok(Self {
+            protocol_version,
+            hardware_version,
+            software_version,
+            software_vcs_revision_id,
+            unique_id,
+            name,
+            software_image_crc,
+            certificate_of_authenticity,
+        })
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Ask `channel` for its `uavcan.node.GetInfo`. Returns `None` if it does not answer within `timeout`.
+    pub fn get_info(&mut self, channel: u8, timeout: Duration) -> Result<Option<NodeInfo>, crate::Error> {
+        let transfer_id: u8 = self.send_request_with_transfer_id(GET_INFO_SERVICE_ID, channel, &[])?;
+
+        match self.wait_response(GET_INFO_SERVICE_ID, channel, transfer_id, timeout)? {
+            Some(frame) => NodeInfo::deserialize(&frame.payload[..frame.payload_size]).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// Probe every node ID in `range` with GetInfo and return the nodes that answered within `timeout`.
+    ///
+    /// All requests are sent first, so the whole scan takes about one `timeout`.
+    pub fn discover(&mut self, range: RangeInclusive<u8>, timeout: Duration) -> Result<Vec<(u8, NodeInfo)>, crate::Error> {
+        let node_id: u8 = self.node_id;
+        let mut pending: Vec<(u8, u8)> = vec![];
+        for channel in range.filter(|channel| (*channel <= CYPHAL_NODE_ID_MAX) & (*channel != node_id)) {
+            pending.push((channel, self.send_request_with_transfer_id(GET_INFO_SERVICE_ID, channel, &[])?));
+        }
+
+        let deadline: Instant = Instant::now() + timeout;
+        let mut ret: Vec<(u8, NodeInfo)> = vec![];
+
+        while !pending.is_empty() {
+            self.load_frames()?;
+
+            pending.retain(|(channel, transfer_id)| {
+                let frame: Option<TimestampedRxFrame> = self.take_response(GET_INFO_SERVICE_ID, *channel, *transfer_id);
+                match frame.map(|frame| NodeInfo::deserialize(&frame.payload[..frame.payload_size])) {
+                    Some(Ok(info)) => {
+                        ret.push((*channel, info));
+                        false
+                    },
+                    Some(Err(_)) => false,
+                    None => true
+                }
+            });
+
+            if Instant::now() >= deadline {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        ret.sort_by_key(|(channel, _)| *channel);
+        Ok(ret)
+    }
+}
@@ -0,0 +1,158 @@
+use std::time::{Duration, Instant};
+
+/// Subject ID of `uavcan.node.Heartbeat.1.0`.
+pub const HEARTBEAT_SUBJECT_ID: u16 = 7509;
+
+/// Default publication period; the Cyphal specification requires at least one heartbeat per second.
+pub const HEARTBEAT_PERIOD: Duration = Duration::from_secs(1);
+
+const HEARTBEAT_SIZE_BYTES: usize = 7;
+
+/// `uavcan.node.Health.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    Nominal,
+    Advisory,
+    Caution,
+    Warning,
+}
+
+impl From<u8> for Health {
+    fn from(x: u8) -> Self {
+        match x & 0x03 {
+            0 => Health::Nominal,
+            1 => Health::Advisory,
+            2 => Health::Caution,
+            _ => Health::Warning,
+        }
+    }
+}
+
+/// `uavcan.node.Mode.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Operational,
+    Initialization,
+    Maintenance,
+    SoftwareUpdate,
+    /// Values reserved by the specification.
+    Reserved(u8),
+}
+
+impl From<u8> for Mode {
+    fn from(x: u8) -> Self {
+        match x & 0x07 {
+            0 => Mode::Operational,
+            1 => Mode::Initialization,
+            2 => Mode::Maintenance,
+            3 => Mode::SoftwareUpdate,
+            x => Mode::Reserved(x),
+        }
+    }
+}
+
+impl From<Mode> for u8 {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Operational => 0,
+            Mode::Initialization => 1,
+            Mode::Maintenance => 2,
+            Mode::SoftwareUpdate => 3,
+            Mode::Reserved(x) => x & 0x07,
+        }
+    }
+}
+
+/// `uavcan.node.Heartbeat.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// Seconds since the node started.
+    pub uptime: u32,
+    pub health: Health,
+    pub mode: Mode,
+    pub vendor_specific_status_code: u8,
+}
+
+impl Heartbeat {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut ret: Vec<u8> = self.uptime.to_le_bytes().to_vec();
+        ret.push(self.health as u8);
+        ret.push(self.mode.into());
+        ret.push(self.vendor_specific_status_code);
+        ret
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        if bytearray.len() < HEARTBEAT_SIZE_BYTES {
+            return Err(crate::Error::Serialization(format!("heartbeat needs {} bytes, got {}", HEARTBEAT_SIZE_BYTES, bytearray.len())));
+        }
+
+        Ok(Self {
+            uptime: u32::from_le_bytes([bytearray[0], bytearray[1], bytearray[2], bytearray[3]]),
+            health: Health::from(bytearray[4]),
+            mode: Mode::from(bytearray[5]),
+            vendor_specific_status_code: bytearray[6],
+        })
+    }
+}
+
+/// Periodic heartbeat of the host node.
+///
+/// Call `tick` regularly, or hand the publisher to `CANRuntime::start_heartbeat`.
+#[derive(Debug, Clone)]
+pub struct HeartbeatPublisher {
+    pub health: Health,
+    pub mode: Mode,
+    pub vendor_specific_status_code: u8,
+    pub period: Duration,
+    started: Instant,
+    next: Option<Instant>,
+}
+
+impl Default for HeartbeatPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeartbeatPublisher {
+    pub fn new() -> Self {
+        Self {
+            health: Health::Nominal,
+            mode: Mode::Operational,
+            vendor_specific_status_code: 0,
+            period: HEARTBEAT_PERIOD,
+            started: Instant::now(),
+            next: None,
+        }
+    }
+
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    pub fn heartbeat(&self, now: Instant) -> Heartbeat {
+        Heartbeat {
+            uptime: now.saturating_duration_since(self.started).as_secs() as u32,
+            health: self.health,
+            mode: self.mode,
+            vendor_specific_status_code: self.vendor_specific_status_code,
+        }
+    }
+
+    /// Publish a heartbeat now.
+    pub fn publish<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>, now: Instant) -> Result<(), crate::Error> {
+        self.next = Some(now + self.period);
+        interface.send_message(HEARTBEAT_SUBJECT_ID, &self.heartbeat(now).serialize())
+    }
+
+    /// Publish a heartbeat if one is due. Returns whether a heartbeat was sent.
+    pub fn tick<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>) -> Result<bool, crate::Error> {
+        let now: Instant = Instant::now();
+        match self.next.is_none_or(|next| now >= next) {
+            true => self.publish(interface, now).map(|_| true),
+            false => Ok(false)
+        }
+    }
+}
@@ -0,0 +1,33 @@
+mod dsdl;
+
+mod heartbeat;
+pub use heartbeat::{Health, Heartbeat, HeartbeatPublisher, Mode, HEARTBEAT_PERIOD, HEARTBEAT_SUBJECT_ID};
+
+mod node_table;
+pub use node_table::{NodeEvent, NodeMonitor, NodeStatus, NodeTable, OFFLINE_TIMEOUT};
+
+mod get_info;
+pub use get_info::{NodeInfo, Version, GET_INFO_SERVICE_ID};
+
+mod register;
+pub use register::{Register, RegisterAccessRequest, Value, REGISTER_ACCESS_SERVICE_ID, REGISTER_LIST_SERVICE_ID};
+
+mod execute_command;
+pub use execute_command::{Command, CommandStatus, ExecuteCommandRequest, EXECUTE_COMMAND_SERVICE_ID};
+
+mod pnp;
+pub use pnp::{NodeIdAllocationData, PnpAllocator, PnpServer, PNP_ALLOCATION_SUBJECT_ID, PNP_DEFAULT_RANGE};
+
+mod time_sync;
+pub use time_sync::{synchronized_time, Synchronization, TimeSyncEstimator, TimeSyncPublisher, TIME_SYNC_PERIOD, TIME_SYNC_SUBJECT_ID};
+
+mod diagnostic;
+pub use diagnostic::{DiagnosticRecord, Severity, DIAGNOSTIC_RECORD_SUBJECT_ID};
+#[cfg(feature="log")]
+pub use diagnostic::DiagnosticLogger;
+
+mod file;
+pub use file::{FileError, FileInfo, FileReadEvent, FileReadRequest, FileReadResponse, FileServer, FileServerTask, FILE_GET_INFO_SERVICE_ID, FILE_READ_CHUNK_SIZE, FILE_READ_SERVICE_ID};
+
+mod firmware;
+pub use firmware::{UpdateProgress, FIRMWARE_UPDATE_TIMEOUT};
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use cands_transport::cyphal::{CyphalTransferKind, CYPHAL_NODE_ID_MAX};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::{Health, Heartbeat, Mode, HEARTBEAT_SUBJECT_ID};
+use crate::{TimestampedRxData, TimestampedRxFrame};
+
+/// A node is considered silent after this long without a heartbeat (`uavcan.node.Heartbeat.1.0` OFFLINE_TIMEOUT).
+pub const OFFLINE_TIMEOUT: Duration = Duration::from_secs(3);
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Last known state of a node, from its most recent heartbeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeStatus {
+    pub last_seen: Instant,
+    pub uptime: u32,
+    pub health: Health,
+    pub mode: Mode,
+    pub vendor_specific_status_code: u8,
+    /// False once the node has been silent for longer than the table's silence timeout.
+    pub online: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeEvent {
+    /// First heartbeat from a node, or the first one after it went silent.
+    Appeared { node_id: u8, status: NodeStatus },
+    /// The node uptime went backward.
+    Rebooted { node_id: u8, status: NodeStatus },
+    /// No heartbeat within the silence timeout.
+    Silent { node_id: u8, last_seen: Instant },
+}
+
+/// Live table of the nodes on the bus, built from their heartbeats.
+#[derive(Debug, Clone)]
+pub struct NodeTable {
+    nodes: BTreeMap<u8, NodeStatus>,
+    pub silence_timeout: Duration,
+}
+
+impl Default for NodeTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeTable {
+    pub fn new() -> Self {
+        Self { nodes: BTreeMap::new(), silence_timeout: OFFLINE_TIMEOUT }
+    }
+
+    pub fn with_silence_timeout(mut self, silence_timeout: Duration) -> Self {
+        self.silence_timeout = silence_timeout;
+        self
+    }
+
+    pub fn get(&self, node_id: u8) -> Option<&NodeStatus> {
+        self.nodes.get(&node_id)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (u8, &NodeStatus)> {
+        self.nodes.iter().map(|(node_id, status)| (*node_id, status))
+    }
+
+    pub fn online_nodes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.nodes().filter(|(_, status)| status.online).map(|(node_id, _)| node_id)
+    }
+
+    pub fn is_online(&self, node_id: u8) -> bool {
+        self.get(node_id).is_some_and(|status| status.online)
+    }
+
+    /// Record a heartbeat received at `timestamp`.
+    pub fn update(&mut self, node_id: u8, heartbeat: &Heartbeat, timestamp: Instant) -> Option<NodeEvent> {
+        if node_id > CYPHAL_NODE_ID_MAX {
+            return None;
+        }
+
+        let status: NodeStatus = NodeStatus {
+            last_seen: timestamp,
+            uptime: heartbeat.uptime,
+            health: heartbeat.health,
+            mode: heartbeat.mode,
+            vendor_specific_status_code: heartbeat.vendor_specific_status_code,
+            online: true,
+        };
+
+        match self.nodes.insert(node_id, status) {
+            None => Some(NodeEvent::Appeared { node_id, status }),
+            Some(previous) if !previous.online => Some(NodeEvent::Appeared { node_id, status }),
+            Some(previous) if heartbeat.uptime < previous.uptime => Some(NodeEvent::Rebooted { node_id, status }),
+            Some(_) => None,
+        }
+    }
+
+    /// Record a received transfer if it is a heartbeat.
+    pub fn update_from_frame(&mut self, frame: &TimestampedRxFrame) -> Option<NodeEvent> {
+        if (frame.props.transfer_kind != CyphalTransferKind::Message) | (frame.props.port_id != HEARTBEAT_SUBJECT_ID) {
+            return None;
+        }
+        let heartbeat: Heartbeat = Heartbeat::deserialize(&frame.payload[..frame.payload_size]).ok()?;
+        self.update(frame.props.source_node_id, &heartbeat, frame.timestamp)
+    }
+
+    /// Mark nodes without a heartbeat since `now - silence_timeout` as offline.
+    pub fn check_silent(&mut self, now: Instant) -> Vec<NodeEvent> {
+        let mut events: Vec<NodeEvent> = vec![];
+        for (node_id, status) in self.nodes.iter_mut() {
+            if status.online && (now.saturating_duration_since(status.last_seen) > self.silence_timeout) {
+                status.online = false;
+                events.push(NodeEvent::Silent { node_id: *node_id, last_seen: status.last_seen });
+            }
+        }
+        events
+    }
+
+    /// Take heartbeats received by `interface` and check for silent nodes.
+    pub fn poll<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>) -> Result<Vec<NodeEvent>, crate::Error> {
+        let mut events: Vec<NodeEvent> = vec![];
+
+        if let Some(heartbeats) = interface.get_heartbeat(None)? {
+            for heartbeat in heartbeats {
+                events.extend(self.update(heartbeat.props.source_node_id, &heartbeat.data, heartbeat.timestamp));
+            }
+        }
+        events.extend(self.check_silent(Instant::now()));
+
+        Ok(events)
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Take received heartbeats, optionally only those of one node.
+    pub fn get_heartbeat(&mut self, source_node_id: Option<u8>) -> Result<Option<Vec<TimestampedRxData<Heartbeat>>>, crate::Error> {
+        let mut buffer: Vec<TimestampedRxData<Heartbeat>> = Vec::new();
+
+        // Load data from a device FIFO and put RxFrames on a user-space FIFO
+        self.load_frames()?;
+
+        self.rx_complete_fifo.retain(|packet| {
+            if (packet.props.transfer_kind != CyphalTransferKind::Message) | (packet.props.port_id != HEARTBEAT_SUBJECT_ID) {
+                return true;
+            }
+            if source_node_id.is_some_and(|node_id| packet.props.source_node_id != node_id) {
+                return true;
+            }
+            if let Ok(data) = Heartbeat::deserialize(&packet.payload[..packet.payload_size]) {
+                buffer.push(TimestampedRxData { data, props: packet.props, timestamp: packet.timestamp });
+            }
+            false
+        });
+
+        match buffer.len() {
+            0 => Ok(None),
+            _ => Ok(Some(buffer))
+        }
+    }
+}
+
+/// A `NodeTable` kept up to date by a background task on a `CANRuntime`.
+pub struct NodeMonitor {
+    table: Arc<Mutex<NodeTable>>,
+    events: broadcast::Sender<NodeEvent>,
+    task: JoinHandle<()>,
+}
+
+impl NodeMonitor {
+    /// Start monitoring heartbeats received by `runtime`. Must be called from within a tokio runtime.
+    pub fn spawn<B: crate::CANBackend>(runtime: &crate::CANRuntime<B>, table: NodeTable) -> Self {
+        let table: Arc<Mutex<NodeTable>> = Arc::new(Mutex::new(table));
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let mut heartbeats = runtime.subscribe(HEARTBEAT_SUBJECT_ID);
+
+        let task: JoinHandle<()> = {
+            let table: Arc<Mutex<NodeTable>> = table.clone();
+            let events: broadcast::Sender<NodeEvent> = events.clone();
+
+            tokio::spawn(async move {
+                let check_period: Duration = (lock(&table).silence_timeout / 4).max(Duration::from_millis(1));
+                let mut check: tokio::time::Interval = tokio::time::interval(check_period);
+
+                loop {
+                    let new_events: Vec<NodeEvent> = tokio::select! {
+                        frame = heartbeats.recv() => match frame {
+                            Ok(frame) => lock(&table).update_from_frame(&frame).into_iter().collect(),
+                            Err(_) => return,
+                        },
+                        _ = check.tick() => lock(&table).check_silent(Instant::now()),
+                    };
+                    for event in new_events {
+                        let _ = events.send(event);
+                    }
+                }
+            })
+        };
+
+        Self { table, events, task }
+    }
+
+    pub fn table(&self) -> MutexGuard<'_, NodeTable> {
+        lock(&self.table)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Drop for NodeMonitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock(table: &Mutex<NodeTable>) -> MutexGuard<'_, NodeTable> {
+    table.lock().unwrap_or_else(|err| err.into_inner())
+}
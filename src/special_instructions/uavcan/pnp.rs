@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cands_transport::cyphal::{CyphalTransferKind, CYPHAL_NODE_ID_MAX};
+use tokio::task::JoinHandle;
+
+use super::dsdl::{Reader, Writer};
+use crate::TimestampedRxFrame;
+
+/// Subject ID of `uavcan.pnp.NodeIDAllocationData.2.0`, the CAN FD variant.
+pub const PNP_ALLOCATION_SUBJECT_ID: u16 = 8165;
+
+/// Node IDs handed out by default. 126 and 127 are left to diagnostic and debugging tools.
+pub const PNP_DEFAULT_RANGE: RangeInclusive<u8> = 1..=125;
+
+/// `uavcan.pnp.NodeIDAllocationData.2.0`
+///
+/// An anonymous node publishes it with its preferred node ID; the allocator answers with the assigned one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeIdAllocationData {
+    pub node_id: u16,
+    pub unique_id: [u8; 16],
+}
+
+impl NodeIdAllocationData {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer.u16(self.node_id).bytes(&self.unique_id);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        Ok(Self { node_id: reader.u16(), unique_id: reader.bytes() })
+    }
+}
+
+/// Plug-and-play node ID allocator.
+///
+/// Each unique ID keeps the node ID it was first given. With `open`, the table is saved to a file
+/// after every new allocation, so a node keeps its ID across restarts of both sides.
+#[derive(Debug, Clone)]
+pub struct PnpAllocator {
+    allocations: BTreeMap<[u8; 16], u8>,
+    occupied: BTreeSet<u8>,
+    path: Option<PathBuf>,
+    pub range: RangeInclusive<u8>,
+}
+
+impl Default for PnpAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PnpAllocator {
+    /// An allocator whose table lives in memory only.
+    pub fn new() -> Self {
+        Self { allocations: BTreeMap::new(), occupied: BTreeSet::new(), path: None, range: PNP_DEFAULT_RANGE }
+    }
+
+    /// An allocator persisted to `path`, loading the table if the file exists.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, crate::Error> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let mut allocator: Self = Self::new();
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => allocator.allocations = parse_table(&text)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(err) => return Err(crate::Error::Storage(err)),
+        }
+        allocator.path = Some(path);
+
+        Ok(allocator)
+    }
+
+    pub fn with_range(mut self, range: RangeInclusive<u8>) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn get(&self, unique_id: &[u8; 16]) -> Option<u8> {
+        self.allocations.get(unique_id).copied()
+    }
+
+    pub fn allocations(&self) -> impl Iterator<Item = (&[u8; 16], u8)> {
+        self.allocations.iter().map(|(unique_id, node_id)| (unique_id, *node_id))
+    }
+
+    /// Never hand out `node_id`, e.g. because a node without an allocation already uses it.
+    pub fn mark_occupied(&mut self, node_id: u8) {
+        self.occupied.insert(node_id);
+    }
+
+    fn is_free(&self, node_id: u8) -> bool {
+        self.range.contains(&node_id) && !self.occupied.contains(&node_id) && !self.allocations.values().any(|x| *x == node_id)
+    }
+
+    /// The node ID for `unique_id`: its previous one, else `preferred` or the nearest free ID above it,
+    /// else the nearest free ID below. Returns `None` when the range is exhausted.
+    pub fn allocate(&mut self, unique_id: [u8; 16], preferred: u16) -> Result<Option<u8>, crate::Error> {
+        if let Some(node_id) = self.get(&unique_id) {
+            return Ok(Some(node_id));
+        }
+
+        let preferred: u8 = preferred.clamp(*self.range.start() as u16, *self.range.end() as u16) as u8;
+        let node_id: Option<u8> = (preferred..=*self.range.end())
+            .chain((*self.range.start()..preferred).rev())
+            .find(|node_id| self.is_free(*node_id));
+
+        if let Some(node_id) = node_id {
+            self.allocations.insert(unique_id, node_id);
+            self.save()?;
+        }
+        Ok(node_id)
+    }
+
+    /// Remove the allocation of `unique_id`, freeing its node ID.
+    pub fn release(&mut self, unique_id: &[u8; 16]) -> Result<Option<u8>, crate::Error> {
+        let node_id: Option<u8> = self.allocations.remove(unique_id);
+        if node_id.is_some() {
+            self.save()?;
+        }
+        Ok(node_id)
+    }
+
+    fn save(&self) -> Result<(), crate::Error> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let mut text: String = String::new();
+        for (unique_id, node_id) in self.allocations.iter() {
+            let unique_id: String = unique_id.iter().map(|byte| format!("{:02x}", byte)).collect();
+            text.push_str(&format!("{} {}\n", unique_id, node_id));
+        }
+
+        // Write then rename, so a crash never leaves a truncated table behind.
+        let temporary: PathBuf = path.with_extension("tmp");
+        std::fs::write(&temporary, text).map_err(crate::Error::Storage)?;
+        std::fs::rename(&temporary, path).map_err(crate::Error::Storage)
+    }
+
+    /// Handle a received transfer: answer allocation requests and remember the node IDs in use.
+    /// Returns the message to publish, if any.
+    pub fn handle_frame(&mut self, frame: &TimestampedRxFrame) -> Result<Option<NodeIdAllocationData>, crate::Error> {
+        if frame.props.source_node_id <= CYPHAL_NODE_ID_MAX {
+            self.occupied.insert(frame.props.source_node_id);
+            return Ok(None);
+        }
+        if !is_allocation_request(frame) {
+            return Ok(None);
+        }
+
+        let request: NodeIdAllocationData = NodeIdAllocationData::deserialize(&frame.payload[..frame.payload_size])?;
+        match self.allocate(request.unique_id, request.node_id)? {
+            Some(node_id) => Ok(Some(NodeIdAllocationData { node_id: node_id as u16, unique_id: request.unique_id })),
+            None => Ok(None)
+        }
+    }
+
+    /// Answer the allocation requests received by `interface`. Returns the allocations sent.
+    pub fn poll<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>) -> Result<Vec<NodeIdAllocationData>, crate::Error> {
+        interface.load_frames()?;
+        self.occupied.insert(interface.node_id);
+
+        let mut requests: Vec<TimestampedRxFrame> = vec![];
+        for frame in std::mem::take(&mut interface.rx_complete_fifo) {
+            match is_allocation_request(&frame) {
+                true => requests.push(frame),
+                false => {
+                    let _ = self.handle_frame(&frame);
+                    interface.rx_complete_fifo.push(frame);
+                }
+            }
+        }
+
+        let mut ret: Vec<NodeIdAllocationData> = vec![];
+        for frame in requests {
+            if let Some(response) = self.handle_frame(&frame)? {
+                interface.send_message(PNP_ALLOCATION_SUBJECT_ID, &response.serialize())?;
+                ret.push(response);
+            }
+        }
+        Ok(ret)
+    }
+}
+
+fn is_allocation_request(frame: &TimestampedRxFrame) -> bool {
+    (frame.props.transfer_kind == CyphalTransferKind::Message)
+        & (frame.props.port_id == PNP_ALLOCATION_SUBJECT_ID)
+        & (frame.props.source_node_id > CYPHAL_NODE_ID_MAX)
+}
+
+fn parse_table(text: &str) -> Result<BTreeMap<[u8; 16], u8>, crate::Error> {
+    let invalid = |line: &str| crate::Error::Storage(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("invalid allocation table entry \"{}\"", line),
+    ));
+
+    let mut ret: BTreeMap<[u8; 16], u8> = BTreeMap::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let (unique_id, node_id) = line.split_once(' ').ok_or_else(|| invalid(line))?;
+        if unique_id.len() != 32 {
+            return Err(invalid(line));
+        }
+        let mut bytes: [u8; 16] = [0; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&unique_id[2 * i..2 * i + 2], 16).map_err(|_| invalid(line))?;
+        }
+        let node_id: u8 = node_id.trim().parse().map_err(|_| invalid(line))?;
+        if node_id > CYPHAL_NODE_ID_MAX {
+            return Err(invalid(line));
+        }
+        ret.insert(bytes, node_id);
+    }
+    Ok(ret)
+}
+
+/// A `PnpAllocator` answering allocation requests in a background task on a `CANRuntime`.
+pub struct PnpServer {
+    allocator: Arc<Mutex<PnpAllocator>>,
+    task: JoinHandle<()>,
+}
+
+impl PnpServer {
+    /// Start serving allocation requests received by `runtime`. Must be called from within a tokio runtime.
+    pub fn spawn<B: crate::CANBackend + Send + 'static>(runtime: &crate::CANRuntime<B>, mut allocator: PnpAllocator) -> Self {
+        allocator.mark_occupied(runtime.interface().node_id);
+        let allocator: Arc<Mutex<PnpAllocator>> = Arc::new(Mutex::new(allocator));
+        let interface = runtime.weak_interface();
+        let mut frames = runtime.subscribe_with(|frame| Some(frame.clone()));
+
+        let task: JoinHandle<()> = {
+            let allocator: Arc<Mutex<PnpAllocator>> = allocator.clone();
+
+            tokio::spawn(async move {
+                while let Ok(frame) = frames.recv().await {
+                    let response: Option<NodeIdAllocationData> = match lock(&allocator).handle_frame(&frame) {
+                        Ok(response) => response,
+                        Err(_) => continue,
+                    };
+                    if let Some(response) = response {
+                        // The runtime is stopping once the interface is gone.
+                        let Some(interface) = interface.upgrade() else { break };
+                        let mut interface = interface.lock().unwrap_or_else(|err| err.into_inner());
+                        let _ = interface.send_message(PNP_ALLOCATION_SUBJECT_ID, &response.serialize());
+                    }
+                }
+            })
+        };
+
+        Self { allocator, task }
+    }
+
+    pub fn allocator(&self) -> MutexGuard<'_, PnpAllocator> {
+        lock(&self.allocator)
+    }
+}
+
+impl Drop for PnpServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock(allocator: &Mutex<PnpAllocator>) -> MutexGuard<'_, PnpAllocator> {
+    allocator.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use super::dsdl::{f16_to_f32, f32_to_f16, Reader, Writer};
+
+/// Service ID of `uavcan.register.Access.1.0`.
+pub const REGISTER_ACCESS_SERVICE_ID: u16 = 384;
+/// Service ID of `uavcan.register.List.1.0`.
+pub const REGISTER_LIST_SERVICE_ID: u16 = 385;
+
+const NAME_MAX_LEN: usize = 255;
+const STRING_MAX_LEN: usize = 256;
+const BIT_MAX_LEN: usize = 2048;
+
+/// `uavcan.register.Value.1.0`. Arrays longer than the DSDL capacity are truncated on serialization.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    /// Also used to read a register without modifying it.
+    #[default]
+    Empty,
+    String(String),
+    Unstructured(Vec<u8>),
+    Bit(Vec<bool>),
+    Integer64(Vec<i64>),
+    Integer32(Vec<i32>),
+    Integer16(Vec<i16>),
+    Integer8(Vec<i8>),
+    Natural64(Vec<u64>),
+    Natural32(Vec<u32>),
+    Natural16(Vec<u16>),
+    Natural8(Vec<u8>),
+    Real64(Vec<f64>),
+    Real32(Vec<f32>),
+    /// Carried as binary16 on the wire.
+    Real16(Vec<f32>),
+}
+
+macro_rules! write_array {
+    ($writer:expr, $values:expr, $max_len:expr, $to_bytes:expr) => {{
+        let writer: &mut Writer = $writer;
+        let values = &$values[..$values.len().min($max_len)];
+        writer.len(values.len(), $max_len);
+        for x in values {
+            writer.bytes(&$to_bytes(*x));
+        }
+    }};
+}
+
+macro_rules! read_array {
+    ($reader:expr, $max_len:expr, $from_bytes:expr) => {{
+        let len: usize = $reader.len($max_len)?;
+        (0..len).map(|_| $from_bytes($reader.bytes())).collect()
+    }};
+}
+
+impl Value {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Value::Empty)
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            Value::Empty => {
+                writer.u8(0);
+            },
+            Value::String(x) => {
+                writer.u8(1).array_u8(x.as_bytes(), STRING_MAX_LEN);
+            },
+            Value::Unstructured(x) => {
+                writer.u8(2).array_u8(x, STRING_MAX_LEN);
+            },
+            Value::Bit(x) => {
+                let x: &[bool] = &x[..x.len().min(BIT_MAX_LEN)];
+                let mut packed: Vec<u8> = vec![0; x.len().div_ceil(8)];
+                for (i, bit) in x.iter().enumerate() {
+                    packed[i / 8] |= (*bit as u8) << (i % 8);
+                }
+                writer.u8(3).len(x.len(), BIT_MAX_LEN).bytes(&packed);
+            },
+            Value::Integer64(x) => write_array!(writer.u8(4), x, 32, i64::to_le_bytes),
+            Value::Integer32(x) => write_array!(writer.u8(5), x, 64, i32::to_le_bytes),
+            Value::Integer16(x) => write_array!(writer.u8(6), x, 128, i16::to_le_bytes),
+            Value::Integer8(x) => write_array!(writer.u8(7), x, 256, i8::to_le_bytes),
+            Value::Natural64(x) => write_array!(writer.u8(8), x, 32, u64::to_le_bytes),
+            Value::Natural32(x) => write_array!(writer.u8(9), x, 64, u32::to_le_bytes),
+            Value::Natural16(x) => write_array!(writer.u8(10), x, 128, u16::to_le_bytes),
+            Value::Natural8(x) => write_array!(writer.u8(11), x, 256, u8::to_le_bytes),
+            Value::Real64(x) => write_array!(writer.u8(12), x, 32, f64::to_le_bytes),
+            Value::Real32(x) => write_array!(writer.u8(13), x, 64, f32::to_le_bytes),
+            Value::Real16(x) => write_array!(writer.u8(14), x, 128, |x: f32| f32_to_f16(x).to_le_bytes()),
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, crate::Error> {
+        let value: Value = match reader.u8() {
+            0 => Value::Empty,
+            1 => Value::String(String::from_utf8_lossy(&reader.array_u8(STRING_MAX_LEN)?).into_owned()),
+            2 => Value::Unstructured(reader.array_u8(STRING_MAX_LEN)?),
+            3 => {
+                let len: usize = reader.len(BIT_MAX_LEN)?;
+                let packed: Vec<u8> = (0..len.div_ceil(8)).map(|_| reader.u8()).collect();
+                Value::Bit((0..len).map(|i| (packed[i / 8] >> (i % 8)) & 1 == 1).collect())
+            },
+            4 => Value::Integer64(read_array!(reader, 32, i64::from_le_bytes)),
+            5 => Value::Integer32(read_array!(reader, 64, i32::from_le_bytes)),
+            6 => Value::Integer16(read_array!(reader, 128, i16::from_le_bytes)),
+            7 => Value::Integer8(read_array!(reader, 256, i8::from_le_bytes)),
+            8 => Value::Natural64(read_array!(reader, 32, u64::from_le_bytes)),
+            9 => Value::Natural32(read_array!(reader, 64, u32::from_le_bytes)),
+            10 => Value::Natural16(read_array!(reader, 128, u16::from_le_bytes)),
+            11 => Value::Natural8(read_array!(reader, 256, u8::from_le_bytes)),
+            12 => Value::Real64(read_array!(reader, 32, f64::from_le_bytes)),
+            13 => Value::Real32(read_array!(reader, 64, f32::from_le_bytes)),
+            14 => Value::Real16(read_array!(reader, 128, |x| f16_to_f32(u16::from_le_bytes(x)))),
+            tag => return Err(crate::Error::Serialization(format!("invalid register value tag {}", tag)))
+        };
+        Ok(value)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        self.write(&mut writer);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        Self::read(&mut Reader::new(bytearray))
+    }
+}
+
+/// Request of `uavcan.register.Access.1.0`. An empty `value` reads the register.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RegisterAccessRequest {
+    pub name: String,
+    pub value: Value,
+}
+
+impl RegisterAccessRequest {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer.array_u8(self.name.as_bytes(), NAME_MAX_LEN);
+        self.value.write(&mut writer);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let name: String = String::from_utf8_lossy(&reader.array_u8(NAME_MAX_LEN)?).into_owned();
+        let value: Value = Value::read(&mut reader)?;
+        Ok(Self { name, value })
+    }
+}
+
+/// Response of `uavcan.register.Access.1.0`. A nonexistent register reads back as `Value::Empty`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Register {
+    /// Microseconds of synchronized time when the value was sampled, 0 if unknown.
+    pub timestamp: u64,
+    pub mutable: bool,
+    pub persistent: bool,
+    pub value: Value,
+}
+
+impl Register {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer: Writer = Writer::default();
+        writer
+            .bytes(&self.timestamp.to_le_bytes()[..7])
+            .u8((self.mutable as u8) | ((self.persistent as u8) << 1));
+        self.value.write(&mut writer);
+        writer.bytes
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let mut reader: Reader = Reader::new(bytearray);
+        let timestamp: [u8; 7] = reader.bytes();
+        let mut timestamp_bytes: [u8; 8] = [0; 8];
+        timestamp_bytes[..7].copy_from_slice(&timestamp);
+        let flags: u8 = reader.u8();
+        let value: Value = Value::read(&mut reader)?;
+
+        Ok(Self {
+            timestamp: u64::from_le_bytes(timestamp_bytes),
+            mutable: flags & 0x01 != 0,
+            persistent: flags & 0x02 != 0,
+            value,
+        })
+    }
+}
+
+impl<B: crate::CANBackend> crate::CANInterface<B> {
+    /// Write `value` to register `name` of `channel` and return the register state after the write.
+    /// `Value::Empty` only reads. Returns `None` if the node does not answer within `timeout`.
+    pub fn access_register(&mut self, channel: u8, name: &str, value: Value, timeout: Duration) -> Result<Option<Register>, crate::Error> {
+        let request: RegisterAccessRequest = RegisterAccessRequest { name: name.to_string(), value };
+        let transfer_id: u8 = self.send_request_with_transfer_id(REGISTER_ACCESS_SERVICE_ID, channel, &request.serialize())?;
+
+        match self.wait_response(REGISTER_ACCESS_SERVICE_ID, channel, transfer_id, timeout)? {
+            Some(frame) => Register::deserialize(&frame.payload[..frame.payload_size]).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// Read register `name` of `channel`.
+    pub fn read_register(&mut self, channel: u8, name: &str, timeout: Duration) -> Result<Option<Register>, crate::Error> {
+        self.access_register(channel, name, Value::Empty, timeout)
+    }
+
+    /// Name of the register at `index` on `channel`. An empty name means `index` is past the last register.
+    pub fn list_register(&mut self, channel: u8, index: u16, timeout: Duration) -> Result<Option<String>, crate::Error> {
+        let transfer_id: u8 = self.send_request_with_transfer_id(REGISTER_LIST_SERVICE_ID, channel, &index.to_le_bytes())?;
+
+        match self.wait_response(REGISTER_LIST_SERVICE_ID, channel, transfer_id, timeout)? {
+            Some(frame) => {
+                let name: Vec<u8> = Reader::new(&frame.payload[..frame.payload_size]).array_u8(NAME_MAX_LEN)?;
+                Ok(Some(String::from_utf8_lossy(&name).into_owned()))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Enumerate the register names of `channel`. Returns `None` if the node does not answer at all.
+    pub fn list_registers(&mut self, channel: u8, timeout: Duration) -> Result<Option<Vec<String>>, crate::Error> {
+        let mut names: Vec<String> = vec![];
+
+        for index in 0..=u16::MAX {
+            match self.list_register(channel, index, timeout)? {
+                Some(name) if name.is_empty() => break,
+                Some(name) => names.push(name),
+                None if index == 0 => return Ok(None),
+                None => return Err(crate::Error::Timeout { channel, key: format!("register #{}", index), attempts: 1 })
+            }
+        }
+
+        Ok(Some(names))
+    }
+
+    /// Enumerate and read every register of `channel`. Returns `None` if the node does not answer at all.
+    pub fn dump_registers(&mut self, channel: u8, timeout: Duration) -> Result<Option<Vec<(String, Register)>>, crate::Error> {
+        let names: Vec<String> = match self.list_registers(channel, timeout)? {
+            Some(names) => names,
+            None => return Ok(None)
+        };
+
+        let mut ret: Vec<(String, Register)> = Vec::with_capacity(names.len());
+        for name in names {
+            match self.read_register(channel, &name, timeout)? {
+                Some(register) => ret.push((name, register)),
+                None => return Err(crate::Error::Timeout { channel, key: name, attempts: 1 })
+            }
+        }
+
+        Ok(Some(ret))
+    }
+}
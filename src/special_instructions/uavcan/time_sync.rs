@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use cands_transport::cyphal::{CyphalTransferKind, CYPHAL_NODE_ID_MAX, CYPHAL_TRANSFER_ID_MAX};
+
+use super::dsdl::Reader;
+use crate::TimestampedRxFrame;
+
+/// Subject ID of `uavcan.time.Synchronization.1.0`.
+pub const TIME_SYNC_SUBJECT_ID: u16 = 7168;
+/// `uavcan.time.Synchronization.1.0` MAX_PUBLICATION_PERIOD.
+pub const TIME_SYNC_PERIOD: Duration = Duration::from_secs(1);
+
+/// A slave drops a measurement whose two messages are further apart than this (PUBLISHER_TIMEOUT_PERIOD_MULTIPLIER = 2).
+const PUBLISHER_TIMEOUT: Duration = Duration::from_secs(2);
+
+const TIMESTAMP_MASK: u64 = (1 << 56) - 1;
+
+/// `uavcan.time.Synchronization.1.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Synchronization {
+    /// Synchronized time of the master's previous transmission of this subject, in microseconds. 0 if unknown.
+    pub previous_transmission_timestamp_microseconds: u64,
+}
+
+impl Synchronization {
+    pub fn serialize(&self) -> Vec<u8> {
+        (self.previous_transmission_timestamp_microseconds & TIMESTAMP_MASK).to_le_bytes()[..7].to_vec()
+    }
+
+    pub fn deserialize(bytearray: &[u8]) -> Result<Self, crate::Error> {
+        let timestamp: [u8; 7] = Reader::new(bytearray).bytes();
+        let mut bytes: [u8; 8] = [0; 8];
+        bytes[..7].copy_from_slice(&timestamp);
+        Ok(Self { previous_transmission_timestamp_microseconds: u64::from_le_bytes(bytes) })
+    }
+}
+
+/// Microseconds since the UNIX epoch at `instant`, the time base published by `TimeSyncPublisher`.
+pub fn synchronized_time(instant: Instant) -> u64 {
+    let now: Instant = Instant::now();
+    let system_time: SystemTime = match instant <= now {
+        true => SystemTime::now() - now.duration_since(instant),
+        false => SystemTime::now() + instant.duration_since(now),
+    };
+    let micros: u128 = system_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros();
+    (micros as u64) & TIMESTAMP_MASK
+}
+
+/// Time synchronization master.
+///
+/// Each message carries the time of the previous transmission, taken from `CANBackend::transmit_timestamp`
+/// when the backend supports it and from the clock right after `transmit` returns otherwise.
+pub struct TimeSyncPublisher {
+    pub period: Duration,
+    next: Option<Instant>,
+    previous: Option<u64>,
+}
+
+impl Default for TimeSyncPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSyncPublisher {
+    pub fn new() -> Self {
+        Self { period: TIME_SYNC_PERIOD, next: None, previous: None }
+    }
+
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Publish a synchronization message now.
+    pub fn publish<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>, now: Instant) -> Result<(), crate::Error> {
+        self.next = Some(now + self.period);
+
+        let message: Synchronization = Synchronization { previous_transmission_timestamp_microseconds: self.previous.unwrap_or(0) };
+        // An unsent message must not be referenced by the next one.
+        self.previous = None;
+        interface.send_message(TIME_SYNC_SUBJECT_ID, &message.serialize())?;
+
+        let transmitted: Instant = interface.driver.transmit_timestamp().unwrap_or_else(Instant::now);
+        self.previous = Some(synchronized_time(transmitted));
+        Ok(())
+    }
+
+    /// Publish a synchronization message if one is due. Returns whether a message was sent.
+    pub fn tick<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>) -> Result<bool, crate::Error> {
+        let now: Instant = Instant::now();
+        match self.next.is_none_or(|next| now >= next) {
+            true => self.publish(interface, now).map(|_| true),
+            false => Ok(false)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Received {
+    master: u8,
+    transfer_id: u8,
+    timestamp: Instant,
+}
+
+/// Time synchronization slave: tracks the master with the lowest node ID and maps local time to its time.
+#[derive(Debug, Clone, Default)]
+pub struct TimeSyncEstimator {
+    previous: Option<Received>,
+    /// Master time at a local instant, from the latest valid measurement.
+    reference: Option<(Instant, u64)>,
+}
+
+impl TimeSyncEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Node ID of the master being followed.
+    pub fn master(&self) -> Option<u8> {
+        self.previous.map(|previous| previous.master)
+    }
+
+    /// Record a message from `master` received at `timestamp`. Returns true if it completed a measurement.
+    pub fn update(&mut self, master: u8, transfer_id: u8, message: &Synchronization, timestamp: Instant) -> bool {
+        if master > CYPHAL_NODE_ID_MAX {
+            return false;
+        }
+        if let Some(previous) = self.previous {
+            let stale: bool = timestamp.saturating_duration_since(previous.timestamp) > PUBLISHER_TIMEOUT;
+            if (master > previous.master) & !stale {
+                return false;
+            }
+        }
+
+        let measurement: Option<Received> = self.previous.filter(|previous| {
+            (previous.master == master)
+                & (transfer_id == (previous.transfer_id + 1) & CYPHAL_TRANSFER_ID_MAX)
+                & (timestamp.saturating_duration_since(previous.timestamp) <= PUBLISHER_TIMEOUT)
+                & (message.previous_transmission_timestamp_microseconds != 0)
+        });
+        // The message carries the master time of the previous one, which we received at `previous.timestamp`.
+        if let Some(previous) = measurement {
+            self.reference = Some((previous.timestamp, message.previous_transmission_timestamp_microseconds));
+        }
+
+        self.previous = Some(Received { master, transfer_id: transfer_id & CYPHAL_TRANSFER_ID_MAX, timestamp });
+        measurement.is_some()
+    }
+
+    /// Record a received transfer if it is a synchronization message.
+    pub fn update_from_frame(&mut self, frame: &TimestampedRxFrame) -> bool {
+        if (frame.props.transfer_kind != CyphalTransferKind::Message) | (frame.props.port_id != TIME_SYNC_SUBJECT_ID) {
+            return false;
+        }
+        match Synchronization::deserialize(&frame.payload[..frame.payload_size]) {
+            Ok(message) => self.update(frame.props.source_node_id, frame.props.transfer_id, &message, frame.timestamp),
+            Err(_) => false
+        }
+    }
+
+    /// Master time at a local `instant`, in microseconds. `None` until the first measurement.
+    pub fn master_time(&self, instant: Instant) -> Option<u64> {
+        let (reference, master_time) = self.reference?;
+        let elapsed: i128 = match instant >= reference {
+            true => instant.duration_since(reference).as_micros() as i128,
+            false => -(reference.duration_since(instant).as_micros() as i128),
+        };
+        Some((master_time as i128 + elapsed).max(0) as u64)
+    }
+
+    /// Master time minus the local `synchronized_time`, in microseconds.
+    pub fn offset(&self) -> Option<i64> {
+        let now: Instant = Instant::now();
+        Some(self.master_time(now)? as i64 - synchronized_time(now) as i64)
+    }
+
+    /// Take synchronization messages received by `interface`. Returns true if any completed a measurement.
+    pub fn poll<B: crate::CANBackend>(&mut self, interface: &mut crate::CANInterface<B>) -> Result<bool, crate::Error> {
+        interface.load_frames()?;
+
+        let mut measured: bool = false;
+        interface.rx_complete_fifo.retain(|frame| {
+            if (frame.props.transfer_kind != CyphalTransferKind::Message) | (frame.props.port_id != TIME_SYNC_SUBJECT_ID) {
+                return true;
+            }
+            measured |= self.update_from_frame(frame);
+            false
+        });
+        Ok(measured)
+    }
+}
@@ -0,0 +1,85 @@
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::{CANBackend, CANInterfaceBuilder, Error, RxData, SIDConfig, XIDConfig};
+
+/// Backend that fails the test if the interface tries to use it.
+struct UntouchedBackend;
+
+impl CANBackend for UntouchedBackend {
+    fn setup(&mut self, _sidf: &[SIDConfig], _xidf: &[XIDConfig]) -> Result<(), Error> {
+        panic!("backend set up with an invalid configuration");
+    }
+
+    fn transmit(&mut self, _xid: u32, _payload: &[u8], _size: usize) -> std::io::Result<()> {
+        panic!("backend used with an invalid configuration");
+    }
+
+    fn receive(&mut self) -> std::io::Result<Option<RxData>> {
+        panic!("backend used with an invalid configuration");
+    }
+}
+
+#[test]
+fn defaults_match_with_backend() {
+    let interface = CANInterfaceBuilder::new().build(MockBackend::new()).unwrap();
+    assert_eq!(interface.node_id, 127);
+    assert_eq!(interface.driver.setup_count, 1);
+    assert!(interface.middleware.transfer_id < 32);
+}
+
+#[test]
+fn node_id_is_used_as_source() {
+    let mut interface = CANInterfaceBuilder::new()
+        .node_id(42)
+        .initial_transfer_id(5)
+        .build(MockBackend::new())
+        .unwrap();
+    assert_eq!(interface.middleware.transfer_id, 5);
+
+    interface.send_message(0x100, &[1, 2, 3]).unwrap();
+
+    let frame = interface.driver.take_transmitted().remove(0);
+    assert_eq!(frame.xid & 0x7F, 42);
+    assert_eq!(frame.payload.last().unwrap() & 0x1F, 5);
+}
+
+#[test]
+fn invalid_node_id_is_rejected() {
+    let err = CANInterfaceBuilder::new().node_id(128).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
+
+#[test]
+fn invalid_transfer_id_is_rejected() {
+    let err = CANInterfaceBuilder::new().initial_transfer_id(32).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
+
+#[test]
+fn invalid_filters_are_rejected() {
+    let sidf = SIDConfig { sft: 3, sfec: 0, sidf1: 0x800, sidf2: 0x456 };
+    let err = CANInterfaceBuilder::new().sid_filters([sidf, sidf]).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+
+    let xidf = XIDConfig { eft: 0, efec: 8, eidf1: 0x55555, eidf2: 0x77777 };
+    let err = CANInterfaceBuilder::new().xid_filters([xidf]).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
+
+#[test]
+fn timeout_and_retry_count_are_applied() {
+    use std::time::Duration;
+
+    let interface = CANInterfaceBuilder::new()
+        .timeout(Duration::from_millis(7))
+        .retry_count(4)
+        .build(MockBackend::new())
+        .unwrap();
+    assert_eq!(interface.timeout, Duration::from_millis(7));
+    assert_eq!(interface.retry_count, 4);
+
+    let err = CANInterfaceBuilder::new().retry_count(0).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+
+    let err = CANInterfaceBuilder::new().timeout(Duration::ZERO).build(UntouchedBackend).err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
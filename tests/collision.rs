@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::{CANInterfaceBuilder, CollisionPolicy, Error};
+
+mod common;
+
+const WINDOW: Duration = Duration::from_millis(10);
+
+fn backend_hearing(node_ids: &[u8]) -> MockBackend {
+    let mut backend = MockBackend::new();
+    for node_id in node_ids {
+        backend.push_rx(common::message(*node_id, 7509, &[0; 7]));
+    }
+    backend
+}
+
+#[test]
+fn collision_fails_startup() {
+    let err = CANInterfaceBuilder::new()
+        .collision_check(WINDOW, CollisionPolicy::Fail)
+        .build(backend_hearing(&[3, 127]))
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::NodeIdCollision { node_id: 127 }));
+}
+
+#[test]
+fn free_node_id_is_picked() {
+    let mut interface = CANInterfaceBuilder::new()
+        .collision_check(WINDOW, CollisionPolicy::PickFree)
+        .build(backend_hearing(&[127, 126]))
+        .unwrap();
+    assert_eq!(interface.node_id, 125);
+
+    interface.send_message(100, &[1]).unwrap();
+    let frame = interface.driver.take_transmitted().remove(0);
+    assert_eq!(common::decode(&frame).props.source_node_id, 125);
+}
+
+#[test]
+fn traffic_from_others_is_kept() {
+    let interface = CANInterfaceBuilder::new()
+        .collision_check(WINDOW, CollisionPolicy::Fail)
+        .build(backend_hearing(&[3, 4]))
+        .unwrap();
+    assert_eq!(interface.node_id, 127);
+    assert_eq!(interface.rx_complete_fifo.len(), 2);
+    assert!(interface.driver.transmitted.is_empty());
+
+    let err = CANInterfaceBuilder::new().collision_check(Duration::ZERO, CollisionPolicy::Fail).validate().err().unwrap();
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
@@ -0,0 +1,43 @@
+//! Frames exchanged with the nodes simulated by the integration tests.
+#![allow(dead_code)]
+
+use cands_cyphal::backend::{packets_to_fifo, MockBackend, TxFrame};
+use cands_cyphal::{CANInterface, CyphalMiddleware, TimestampedRxFrame};
+use cands_transport::cyphal::CyphalRxPacket;
+
+/// Node ID `CANInterface` uses unless configured otherwise.
+pub const HOST_NODE_ID: u8 = 127;
+
+/// Decode a transmitted single-frame transfer the way a node on the bus would see it.
+pub fn decode(frame: &TxFrame) -> CyphalRxPacket<64> {
+    CyphalMiddleware::<64>::new(0).try_read(&frame.to_fifo_element().unwrap()).unwrap().remove(0)
+}
+
+/// Reassemble transmitted frames into the transfers a node on the bus would receive.
+pub fn transfers(frames: &[TxFrame]) -> Vec<TimestampedRxFrame> {
+    let mut rx = CANInterface::with_backend(MockBackend::new()).unwrap();
+    for frame in frames {
+        rx.load_frames_from_buffer(&frame.to_fifo_element().unwrap()).unwrap();
+    }
+    std::mem::take(&mut rx.rx_complete_fifo)
+}
+
+/// FIFO buffer of a response from `source_node_id` to the host.
+pub fn response(source_node_id: u8, service_id: u16, transfer_id: u8, payload: &[u8]) -> Vec<u8> {
+    let mut middleware = CyphalMiddleware::<64>::new(source_node_id);
+    middleware.transfer_id = transfer_id;
+    packets_to_fifo(&middleware.create_response_data(HOST_NODE_ID, service_id, payload, payload.len()).unwrap())
+}
+
+/// FIFO buffer answering `request` on its own service, as its addressee must.
+pub fn respond(request: &CyphalRxPacket<64>, payload: &[u8]) -> Vec<u8> {
+    let mut middleware = CyphalMiddleware::<64>::new(request.props.destination_node_id);
+    middleware.transfer_id = request.props.transfer_id;
+    packets_to_fifo(&middleware.create_response_data(request.props.source_node_id, request.props.port_id, payload, payload.len()).unwrap())
+}
+
+/// FIFO buffer of a message published by `source_node_id`.
+pub fn message(source_node_id: u8, subject_id: u16, payload: &[u8]) -> Vec<u8> {
+    let mut middleware = CyphalMiddleware::<64>::new(source_node_id);
+    packets_to_fifo(&middleware.create_message_data(subject_id, payload, payload.len()).unwrap())
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::uavcan::{DiagnosticRecord, Severity, DIAGNOSTIC_RECORD_SUBJECT_ID};
+use cands_cyphal::{CANInterface, CANRuntime};
+
+mod common;
+
+fn record_from(node_id: u8, severity: Severity, text: &str) -> Vec<u8> {
+    common::message(node_id, DIAGNOSTIC_RECORD_SUBJECT_ID, &DiagnosticRecord { timestamp: 0, severity, text: text.into() }.serialize())
+}
+
+#[test]
+fn record_round_trip() {
+    let record = DiagnosticRecord { timestamp: 0x0001_0203_0405_0607, severity: Severity::Warning, text: "overheat".into() };
+    let bytes = record.serialize();
+    assert_eq!(&bytes[..10], &[0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 4, 8, b'o']);
+    assert_eq!(DiagnosticRecord::deserialize(&bytes).unwrap(), record);
+    assert_eq!(Severity::from(0xFF), Severity::Alert);
+}
+
+#[test]
+fn publish_and_receive() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    interface.publish_diagnostic(Severity::Notice, "host started").unwrap();
+
+    let frame = interface.driver.take_transmitted().remove(0);
+    interface.driver.push_rx([frame.to_fifo_element().unwrap(), record_from(3, Severity::Error, "encoder fault")].concat());
+
+    let records = interface.get_diagnostic(Some(3)).unwrap().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].data.severity, Severity::Error);
+    assert_eq!(records[0].data.text, "encoder fault");
+
+    let records = interface.get_diagnostic(None).unwrap().unwrap();
+    assert_eq!(records[0].props.source_node_id, 127);
+    assert_eq!(records[0].data.text, "host started");
+    assert!(records[0].data.timestamp > 0);
+}
+
+#[tokio::test]
+async fn runtime_subscription_filters_by_node() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+    let mut records = runtime.subscribe_diagnostic(Some(5));
+    runtime.interface().driver.push_rx([record_from(2, Severity::Info, "a"), record_from(5, Severity::Info, "b")].concat());
+
+    let record = tokio::time::timeout(Duration::from_millis(100), records.recv()).await.unwrap().unwrap();
+    assert_eq!(record.data.text, "b");
+}
+
+#[cfg(feature="log")]
+mod logging {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use cands_cyphal::backend::MockBackend;
+    use cands_cyphal::uavcan::{DiagnosticLogger, Severity};
+    use cands_cyphal::{CANInterface, CANRuntime};
+
+    static LOGGED: Mutex<Vec<(log::Level, String, String)>> = Mutex::new(vec![]);
+
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "cands_cyphal::diagnostic"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if !self.enabled(record.metadata()) {
+                return;
+            }
+            LOGGED.lock().unwrap().push((record.level(), record.target().to_string(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[tokio::test]
+    async fn records_are_forwarded_to_log() {
+        log::set_logger(&CaptureLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+        let _logger = DiagnosticLogger::spawn(&runtime);
+        runtime.interface().driver.push_rx(super::record_from(7, Severity::Critical, "undervoltage"));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let logged = LOGGED.lock().unwrap();
+        assert_eq!(*logged, vec![(log::Level::Error, "cands_cyphal::diagnostic".to_string(), "node 7: undervoltage".to_string())]);
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::uavcan::{Command, CommandStatus, ExecuteCommandRequest, EXECUTE_COMMAND_SERVICE_ID};
+use cands_cyphal::{CANInterface, CANInterfaceBuilder, Error};
+
+mod common;
+
+const NODE_ID: u8 = 4;
+
+/// Answer ExecuteCommand requests after ignoring the first `dropped` ones.
+/// Restart succeeds, a software update without a path is a bad parameter and anything else is a bad command.
+fn interface(dropped: usize) -> CANInterface<MockBackend> {
+    let mut interface = CANInterfaceBuilder::new()
+        .timeout(Duration::from_millis(10))
+        .retry_count(3)
+        .build(MockBackend::new())
+        .unwrap();
+
+    let mut requests: usize = 0;
+    interface.driver.set_responder(move |frame: &TxFrame| {
+        let packet = common::decode(frame);
+        if (packet.props.port_id != EXECUTE_COMMAND_SERVICE_ID) | (packet.props.destination_node_id != NODE_ID) {
+            return vec![];
+        }
+        requests += 1;
+        if requests <= dropped {
+            return vec![];
+        }
+
+        let request = ExecuteCommandRequest::deserialize(&packet.payload[..packet.payload_size]).unwrap();
+        let status: CommandStatus = match request.command {
+            Command::Restart => CommandStatus::Success,
+            Command::BeginSoftwareUpdate if request.parameter.is_empty() => CommandStatus::BadParameter,
+            Command::BeginSoftwareUpdate => CommandStatus::Success,
+            _ => CommandStatus::BadCommand,
+        };
+
+        vec![common::respond(&packet, &[status.into()])]
+    });
+    interface
+}
+
+#[test]
+fn request_encoding() {
+    let request = ExecuteCommandRequest { command: Command::BeginSoftwareUpdate, parameter: b"fw.bin".to_vec() };
+    let bytes = request.serialize();
+    assert_eq!(&bytes[..3], &[0xFD, 0xFF, 6]);
+    assert_eq!(ExecuteCommandRequest::deserialize(&bytes).unwrap(), request);
+
+    assert_eq!(Command::from(0x1234), Command::Other(0x1234));
+    assert_eq!(u16::from(Command::StorePersistentStates), 65530);
+    assert_eq!(CommandStatus::from(9), CommandStatus::Other(9));
+}
+
+#[test]
+fn returns_response_status() {
+    let mut interface = interface(0);
+
+    assert_eq!(interface.restart(NODE_ID).unwrap(), CommandStatus::Success);
+    assert_eq!(interface.factory_reset(NODE_ID).unwrap(), CommandStatus::BadCommand);
+    assert_eq!(interface.begin_software_update(NODE_ID, "").unwrap(), CommandStatus::BadParameter);
+    assert_eq!(interface.begin_software_update(NODE_ID, "fw.bin").unwrap(), CommandStatus::Success);
+    assert_eq!(interface.driver.transmitted.len(), 4);
+}
+
+#[test]
+fn retries_lost_requests() {
+    let mut interface = interface(2);
+
+    assert_eq!(interface.restart(NODE_ID).unwrap(), CommandStatus::Success);
+    assert_eq!(interface.driver.transmitted.len(), 3);
+}
+
+#[test]
+fn times_out_after_retry_count() {
+    let mut interface = interface(usize::MAX);
+
+    let err = interface.store_persistent_states(NODE_ID).err().unwrap();
+    assert!(matches!(err, Error::Timeout { channel: NODE_ID, attempts: 3, .. }));
+    assert_eq!(interface.driver.transmitted.len(), 3);
+}
@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cands_cyphal::backend::{packets_to_fifo, MockBackend, TxFrame};
+use cands_cyphal::uavcan::{
+    CommandStatus, ExecuteCommandRequest, FileError, FileInfo, FileReadRequest, FileReadResponse, FileServer, Health, Heartbeat, Mode,
+    UpdateProgress, EXECUTE_COMMAND_SERVICE_ID, FILE_GET_INFO_SERVICE_ID, FILE_READ_CHUNK_SIZE, FILE_READ_SERVICE_ID, HEARTBEAT_SUBJECT_ID,
+};
+use cands_cyphal::{CANInterface, CANInterfaceBuilder, CANRuntime, CyphalMiddleware, Error};
+
+mod common;
+use common::HOST_NODE_ID;
+
+const NODE_ID: u8 = 4;
+
+fn image(name: &str, len: usize) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cands_cyphal_file_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("fw.bin");
+    std::fs::write(&path, (0..len).map(|i| i as u8).collect::<Vec<u8>>()).unwrap();
+    path
+}
+
+fn read_request(node: &mut CyphalMiddleware<64>, offset: u64) -> Vec<u8> {
+    let payload = FileReadRequest { offset, path: "fw.bin".into() }.serialize();
+    packets_to_fifo(&node.create_request_data(HOST_NODE_ID, FILE_READ_SERVICE_ID, &payload, payload.len()).unwrap())
+}
+
+fn heartbeat(node: &mut CyphalMiddleware<64>, health: Health, mode: Mode) -> Vec<u8> {
+    let payload = Heartbeat { uptime: 1, health, mode, vendor_specific_status_code: 0 }.serialize();
+    packets_to_fifo(&node.create_message_data(HEARTBEAT_SUBJECT_ID, &payload, payload.len()).unwrap())
+}
+
+/// A node that accepts `BeginSoftwareUpdate`, reads the image chunk by chunk and reboots with `health`.
+/// With `stale_heartbeat`, an `Operational` heartbeat from before the update arrives together with the first read.
+fn updating_node(health: Health, stale_heartbeat: bool) -> CANInterface<MockBackend> {
+    let mut interface = CANInterfaceBuilder::new()
+        .timeout(Duration::from_millis(10))
+        .build(MockBackend::new())
+        .unwrap();
+
+    // Reassembles the multi-frame read responses on the node side.
+    let mut rx = CANInterface::with_backend(MockBackend::new()).unwrap();
+    let mut node = CyphalMiddleware::<64>::new(NODE_ID);
+    let mut offset: u64 = 0;
+    interface.driver.set_responder(move |frame: &TxFrame| {
+        let mut ret: Vec<Vec<u8>> = vec![];
+        rx.load_frames_from_buffer(&frame.to_fifo_element().unwrap()).unwrap();
+        for packet in std::mem::take(&mut rx.rx_complete_fifo) {
+            let payload = &packet.payload[..packet.payload_size];
+            match packet.props.port_id {
+                EXECUTE_COMMAND_SERVICE_ID => {
+                    assert_eq!(ExecuteCommandRequest::deserialize(payload).unwrap().parameter, b"fw.bin");
+                    let response = common::response(NODE_ID, EXECUTE_COMMAND_SERVICE_ID, packet.props.transfer_id, &[CommandStatus::Success.into()]);
+                    ret.push([response, heartbeat(&mut node, Health::Nominal, Mode::SoftwareUpdate), read_request(&mut node, 0)].concat());
+                    if stale_heartbeat {
+                        ret.last_mut().unwrap().extend(heartbeat(&mut node, Health::Nominal, Mode::Operational));
+                    }
+                },
+                FILE_READ_SERVICE_ID => {
+                    let response = FileReadResponse::deserialize(payload).unwrap();
+                    assert_eq!(response.error, FileError::Ok);
+                    match response.data.len() {
+                        FILE_READ_CHUNK_SIZE => {
+                            offset += FILE_READ_CHUNK_SIZE as u64;
+                            ret.push(read_request(&mut node, offset));
+                        },
+                        _ => ret.push(heartbeat(&mut node, health, Mode::Operational)),
+                    }
+                },
+                _ => {}
+            }
+        }
+        ret
+    });
+    interface
+}
+
+#[test]
+fn read_and_get_info() {
+    let path = image("read", 300);
+    let server = FileServer::new(path.parent().unwrap());
+
+    let response = server.read("fw.bin", 256);
+    assert_eq!(response, FileReadResponse { error: FileError::Ok, data: (256..300).map(|i| i as u8).collect() });
+    assert_eq!(FileReadResponse::deserialize(&response.serialize()).unwrap(), response);
+    assert_eq!(server.read("fw.bin", 0).data.len(), FILE_READ_CHUNK_SIZE);
+    assert_eq!(server.read("missing.bin", 0).error, FileError::NotFound);
+    assert_eq!(server.read("../fw.bin", 0).error, FileError::AccessDenied);
+    assert_eq!(server.read("/etc/hostname", 0).error, FileError::AccessDenied);
+    assert_eq!(server.read("./fw.bin", 0).error, FileError::Ok);
+
+    let info = server.get_info("fw.bin");
+    assert_eq!((info.error, info.size, info.is_file_not_directory, info.is_writeable), (FileError::Ok, 300, true, false));
+    assert_eq!(FileInfo::deserialize(&info.serialize()).unwrap(), info);
+    assert!(!server.get_info("").is_file_not_directory);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_leaving_root_are_denied() {
+    let path = image("symlink", 10);
+    let outside = image("symlink_target", 20);
+    let link = path.with_file_name("outside.bin");
+    let _ = std::fs::remove_file(&link);
+    std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+    let server = FileServer::new(path.parent().unwrap());
+    assert_eq!(server.read("outside.bin", 0).error, FileError::AccessDenied);
+    assert_eq!(server.get_info("outside.bin").error, FileError::AccessDenied);
+    assert_eq!(server.read("fw.bin", 0).error, FileError::Ok);
+}
+
+#[test]
+fn file_server_for_one_file() {
+    let path = image("single", 10);
+    std::fs::write(path.with_file_name("other.bin"), [0u8; 4]).unwrap();
+
+    let server = FileServer::file(&path);
+    assert_eq!(server.read("fw.bin", 0).data.len(), 10);
+    assert_eq!(server.read("./fw.bin", 0).data.len(), 10);
+    assert_eq!(server.read("other.bin", 0).error, FileError::AccessDenied);
+    assert_eq!(server.get_info("").error, FileError::AccessDenied);
+}
+
+#[test]
+fn serve_answers_with_request_transfer_id() {
+    let path = image("serve", 10);
+    let server = FileServer::new(path.parent().unwrap());
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+
+    let mut node = CyphalMiddleware::<64>::new(NODE_ID);
+    node.transfer_id = 17;
+    let get_info: Vec<u8> = [&[6u8][..], b"fw.bin"].concat();
+    let get_info = packets_to_fifo(&node.create_request_data(HOST_NODE_ID, FILE_GET_INFO_SERVICE_ID, &get_info, get_info.len()).unwrap());
+    interface.driver.push_rx([get_info, read_request(&mut node, 4)].concat());
+
+    let events = server.serve(&mut interface).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].node_id, events[0].offset, events[0].len), (NODE_ID, 4, 6));
+
+    let responses: Vec<_> = interface.driver.take_transmitted().iter().map(common::decode).collect();
+    assert_eq!((responses[0].props.port_id, responses[0].props.transfer_id), (FILE_GET_INFO_SERVICE_ID, 17));
+    assert_eq!(FileInfo::deserialize(&responses[0].payload[..responses[0].payload_size]).unwrap().size, 10);
+    assert_eq!((responses[1].props.port_id, responses[1].props.transfer_id), (FILE_READ_SERVICE_ID, 18));
+    assert_eq!(responses[1].props.destination_node_id, NODE_ID);
+}
+
+#[tokio::test]
+async fn stop_with_server_running() {
+    let path = image("stop", 10);
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+    let task = FileServer::new(path.parent().unwrap()).spawn(&runtime);
+    runtime.interface().driver.push_rx(read_request(&mut CyphalMiddleware::<64>::new(NODE_ID), 0));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let mut interface = tokio::time::timeout(Duration::from_secs(1), runtime.stop()).await.unwrap();
+    assert_eq!(common::decode(&interface.driver.take_transmitted()[0]).props.port_id, FILE_READ_SERVICE_ID);
+    drop(task);
+}
+
+#[test]
+fn update_firmware_tracks_progress() {
+    let path = image("update", 600);
+    let mut interface = updating_node(Health::Nominal, false);
+
+    let mut progress: Vec<UpdateProgress> = vec![];
+    interface.update_firmware_with_progress(NODE_ID, &path, Duration::from_millis(200), |state| progress.push(*state)).unwrap();
+
+    let bytes_read: Vec<u64> = progress.iter().map(|state| state.bytes_read).collect();
+    assert_eq!(bytes_read, vec![256, 256, 512, 600, 600]);
+    assert_eq!(progress[1].mode, Some(Mode::SoftwareUpdate));
+    assert_eq!(progress.last().unwrap().mode, Some(Mode::Operational));
+    assert!(progress.iter().all(|state| state.file_size == 600));
+}
+
+#[test]
+fn update_firmware_ignores_heartbeats_before_last_read() {
+    let path = image("stale", 600);
+    let mut interface = updating_node(Health::Nominal, true);
+
+    let mut progress: Vec<UpdateProgress> = vec![];
+    interface.update_firmware_with_progress(NODE_ID, &path, Duration::from_millis(200), |state| progress.push(*state)).unwrap();
+    assert_eq!(progress.last().unwrap().bytes_read, 600);
+}
+
+#[test]
+fn update_firmware_failures() {
+    let path = image("failures", 10);
+
+    let mut interface = updating_node(Health::Warning, false);
+    assert!(matches!(interface.update_firmware(NODE_ID, &path), Err(Error::FirmwareUpdate { channel: NODE_ID, .. })));
+
+    let mut interface = updating_node(Health::Nominal, false);
+    assert!(matches!(interface.update_firmware(NODE_ID, path.with_file_name("missing.bin")), Err(Error::Storage(_))));
+
+    // Nobody answers BeginSoftwareUpdate.
+    let mut interface = CANInterfaceBuilder::new().timeout(Duration::from_millis(5)).build(MockBackend::new()).unwrap();
+    assert!(matches!(interface.update_firmware(3, &path), Err(Error::Timeout { channel: 3, .. })));
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::uavcan::{NodeInfo, Version, GET_INFO_SERVICE_ID};
+use cands_cyphal::CANInterface;
+
+mod common;
+
+const TIMEOUT: Duration = Duration::from_millis(20);
+
+fn node_info(node_id: u8) -> NodeInfo {
+    NodeInfo {
+        protocol_version: Version { major: 1, minor: 0 },
+        hardware_version: Version { major: 2, minor: node_id },
+        software_version: Version { major: 3, minor: 1 },
+        software_vcs_revision_id: 0x0123_4567_89AB_CDEF,
+        unique_id: [node_id; 16],
+        name: format!("com.digitalservo.drive{}", node_id),
+        software_image_crc: Some(0xDEAD_BEEF),
+        certificate_of_authenticity: vec![],
+    }
+}
+
+/// Reply to GetInfo requests addressed to one of `nodes`.
+fn interface(nodes: &'static [u8]) -> CANInterface<MockBackend> {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    interface.driver.set_responder(move |frame: &TxFrame| {
+        let packet = common::decode(frame);
+        let node_id = packet.props.destination_node_id;
+        if (packet.props.port_id != GET_INFO_SERVICE_ID) | !nodes.contains(&node_id) {
+            return vec![];
+        }
+
+        vec![common::respond(&packet, &node_info(node_id).serialize())]
+    });
+    interface
+}
+
+#[test]
+fn node_info_round_trip() {
+    let mut info = node_info(3);
+    assert_eq!(NodeInfo::deserialize(&info.serialize()).unwrap(), info);
+
+    info.software_image_crc = None;
+    info.certificate_of_authenticity = vec![1; 100];
+    assert_eq!(NodeInfo::deserialize(&info.serialize()).unwrap(), info);
+}
+
+#[test]
+fn truncated_node_info_is_zero_extended() {
+    let info = NodeInfo::deserialize(&[1, 0, 2, 3]).unwrap();
+    assert_eq!(info.protocol_version, Version { major: 1, minor: 0 });
+    assert_eq!(info.hardware_version, Version { major: 2, minor: 3 });
+    assert_eq!(info.name, "");
+    assert_eq!(info.software_image_crc, None);
+}
+
+#[test]
+fn get_info_reassembles_response() {
+    let mut interface = interface(&[3]);
+
+    assert_eq!(interface.get_info(3, TIMEOUT).unwrap(), Some(node_info(3)));
+    assert_eq!(interface.get_info(4, TIMEOUT).unwrap(), None);
+    assert!(interface.rx_complete_fifo.is_empty());
+}
+
+#[test]
+fn discover_returns_responding_nodes() {
+    let mut interface = interface(&[2, 5, 9]);
+
+    let nodes = interface.discover(1..=8, TIMEOUT).unwrap();
+
+    assert_eq!(nodes, vec![(2, node_info(2)), (5, node_info(5))]);
+    assert_eq!(interface.driver.transmitted.len(), 8);
+}
@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::uavcan::{Health, Heartbeat, HeartbeatPublisher, Mode, HEARTBEAT_SUBJECT_ID};
+use cands_cyphal::{CANInterfaceBuilder, CANRuntime};
+
+mod common;
+
+const HOST_NODE_ID: u8 = 42;
+
+fn decode(frame: &TxFrame) -> (u16, u8, Heartbeat) {
+    let packet = common::decode(frame);
+    (packet.props.port_id, packet.props.source_node_id, Heartbeat::deserialize(&packet.payload[..packet.payload_size]).unwrap())
+}
+
+#[test]
+fn heartbeat_round_trip() {
+    let heartbeat = Heartbeat { uptime: 0x01020304, health: Health::Caution, mode: Mode::Maintenance, vendor_specific_status_code: 0xAB };
+    let bytes = heartbeat.serialize();
+    assert_eq!(bytes, vec![0x04, 0x03, 0x02, 0x01, 2, 2, 0xAB]);
+    assert_eq!(Heartbeat::deserialize(&bytes).unwrap(), heartbeat);
+    assert!(Heartbeat::deserialize(&bytes[..6]).is_err());
+}
+
+#[test]
+fn tick_publishes_once_per_period() {
+    let mut interface = CANInterfaceBuilder::new().node_id(HOST_NODE_ID).build(MockBackend::new()).unwrap();
+    let mut publisher = HeartbeatPublisher::new().with_period(Duration::from_millis(30));
+    publisher.health = Health::Advisory;
+
+    assert!(publisher.tick(&mut interface).unwrap());
+    assert!(!publisher.tick(&mut interface).unwrap());
+    std::thread::sleep(Duration::from_millis(40));
+    assert!(publisher.tick(&mut interface).unwrap());
+
+    let transmitted = interface.driver.take_transmitted();
+    assert_eq!(transmitted.len(), 2);
+    let (port_id, source_node_id, heartbeat) = decode(&transmitted[0]);
+    assert_eq!(port_id, HEARTBEAT_SUBJECT_ID);
+    assert_eq!(source_node_id, HOST_NODE_ID);
+    assert_eq!(heartbeat.health, Health::Advisory);
+    assert_eq!(heartbeat.mode, Mode::Operational);
+}
+
+#[test]
+fn uptime_counts_seconds() {
+    let publisher = HeartbeatPublisher::new();
+    assert_eq!(publisher.heartbeat(Instant::now() + Duration::from_millis(2500)).uptime, 2);
+}
+
+#[tokio::test]
+async fn runtime_drives_heartbeat() {
+    let interface = CANInterfaceBuilder::new().node_id(HOST_NODE_ID).build(MockBackend::new()).unwrap();
+    let runtime = CANRuntime::spawn(interface);
+
+    runtime.start_heartbeat(HeartbeatPublisher::new().with_period(Duration::from_millis(10)));
+    tokio::time::sleep(Duration::from_millis(35)).await;
+    runtime.update_heartbeat(|publisher| publisher.mode = Mode::SoftwareUpdate);
+    tokio::time::sleep(Duration::from_millis(15)).await;
+    assert!(runtime.stop_heartbeat().is_some());
+
+    let transmitted = runtime.interface().driver.take_transmitted();
+    assert!(transmitted.len() >= 3);
+    assert!(transmitted.iter().all(|frame| decode(frame).0 == HEARTBEAT_SUBJECT_ID));
+    assert_eq!(decode(transmitted.last().unwrap()).2.mode, Mode::SoftwareUpdate);
+}
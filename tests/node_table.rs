@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::uavcan::{Health, Heartbeat, Mode, NodeEvent, NodeMonitor, NodeTable, HEARTBEAT_SUBJECT_ID};
+use cands_cyphal::{CANInterface, CANRuntime};
+
+mod common;
+
+fn heartbeat(uptime: u32) -> Heartbeat {
+    Heartbeat { uptime, health: Health::Nominal, mode: Mode::Operational, vendor_specific_status_code: 0x5A }
+}
+
+fn heartbeat_message(source_node_id: u8, uptime: u32) -> Vec<u8> {
+    common::message(source_node_id, HEARTBEAT_SUBJECT_ID, &heartbeat(uptime).serialize())
+}
+
+#[test]
+fn appeared_rebooted_and_silent() {
+    let mut table = NodeTable::new().with_silence_timeout(Duration::from_secs(3));
+    let t0 = Instant::now();
+
+    assert!(matches!(table.update(3, &heartbeat(10), t0), Some(NodeEvent::Appeared { node_id: 3, .. })));
+    assert_eq!(table.update(3, &heartbeat(11), t0 + Duration::from_secs(1)), None);
+    assert!(matches!(table.update(3, &heartbeat(0), t0 + Duration::from_secs(2)), Some(NodeEvent::Rebooted { node_id: 3, .. })));
+
+    let status = table.get(3).unwrap();
+    assert_eq!(status.uptime, 0);
+    assert_eq!(status.vendor_specific_status_code, 0x5A);
+    assert!(table.is_online(3));
+
+    assert!(table.check_silent(t0 + Duration::from_secs(4)).is_empty());
+    assert_eq!(table.check_silent(t0 + Duration::from_secs(6)), vec![NodeEvent::Silent { node_id: 3, last_seen: t0 + Duration::from_secs(2) }]);
+    assert!(!table.is_online(3));
+    assert!(table.check_silent(t0 + Duration::from_secs(7)).is_empty());
+
+    assert!(matches!(table.update(3, &heartbeat(5), t0 + Duration::from_secs(8)), Some(NodeEvent::Appeared { node_id: 3, .. })));
+}
+
+#[test]
+fn poll_consumes_heartbeats_only() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    let mut buffer = heartbeat_message(3, 1);
+    buffer.extend(heartbeat_message(4, 1));
+    buffer.extend(common::message(5, 0x100, &[1]));
+    interface.driver.push_rx(buffer);
+
+    let mut table = NodeTable::new();
+    let events = table.poll(&mut interface).unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(table.online_nodes().collect::<Vec<u8>>(), vec![3, 4]);
+    assert_eq!(interface.rx_complete_fifo.len(), 1);
+}
+
+#[tokio::test]
+async fn monitor_raises_events() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+    let monitor = NodeMonitor::spawn(&runtime, NodeTable::new().with_silence_timeout(Duration::from_millis(40)));
+    let mut events = monitor.subscribe();
+
+    runtime.interface().driver.push_rx(heartbeat_message(7, 100));
+    let event = tokio::time::timeout(Duration::from_millis(500), events.recv()).await.unwrap().unwrap();
+    assert!(matches!(event, NodeEvent::Appeared { node_id: 7, .. }));
+    assert!(monitor.table().is_online(7));
+
+    let event = tokio::time::timeout(Duration::from_millis(500), events.recv()).await.unwrap().unwrap();
+    assert!(matches!(event, NodeEvent::Silent { node_id: 7, .. }));
+    assert!(!monitor.table().is_online(7));
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::uavcan::{NodeIdAllocationData, PnpAllocator, PnpServer, PNP_ALLOCATION_SUBJECT_ID};
+use cands_cyphal::{CANInterface, CANRuntime, Error};
+
+mod common;
+use common::HOST_NODE_ID;
+
+fn table_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cands_cyphal_pnp_{}_{}.txt", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// An anonymous allocation request from `unique_id`.
+fn request(unique_id: [u8; 16], preferred: u16) -> Vec<u8> {
+    common::message(255, PNP_ALLOCATION_SUBJECT_ID, &NodeIdAllocationData { node_id: preferred, unique_id }.serialize())
+}
+
+fn heartbeat_from(node_id: u8) -> Vec<u8> {
+    common::message(node_id, 7509, &[0; 7])
+}
+
+fn decode(frame: &TxFrame) -> NodeIdAllocationData {
+    let packet = common::decode(frame);
+    assert_eq!(packet.props.port_id, PNP_ALLOCATION_SUBJECT_ID);
+    assert_eq!(packet.props.source_node_id, HOST_NODE_ID);
+    NodeIdAllocationData::deserialize(&packet.payload[..packet.payload_size]).unwrap()
+}
+
+#[test]
+fn allocates_preferred_or_nearest_free() {
+    let mut allocator = PnpAllocator::new().with_range(10..=12);
+
+    assert_eq!(allocator.allocate([1; 16], 11).unwrap(), Some(11));
+    assert_eq!(allocator.allocate([2; 16], 11).unwrap(), Some(12));
+    assert_eq!(allocator.allocate([3; 16], 11).unwrap(), Some(10));
+    assert_eq!(allocator.allocate([4; 16], 11).unwrap(), None);
+    assert_eq!(allocator.allocate([2; 16], 0xFFFF).unwrap(), Some(12));
+
+    assert_eq!(allocator.release(&[1; 16]).unwrap(), Some(11));
+    allocator.mark_occupied(11);
+    assert_eq!(allocator.allocate([4; 16], 11).unwrap(), None);
+}
+
+#[test]
+fn poll_answers_anonymous_requests() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    interface.driver.push_rx([heartbeat_from(5), request([7; 16], 5)].concat());
+    let mut allocator = PnpAllocator::new();
+
+    let allocations = allocator.poll(&mut interface).unwrap();
+
+    assert_eq!(allocations, vec![NodeIdAllocationData { node_id: 6, unique_id: [7; 16] }]);
+    assert_eq!(decode(&interface.driver.transmitted[0]), allocations[0]);
+    // Other traffic is left for the application.
+    assert_eq!(interface.rx_complete_fifo.len(), 1);
+}
+
+#[test]
+fn table_is_persisted() {
+    let path = table_path("persist");
+
+    let mut allocator = PnpAllocator::open(&path).unwrap();
+    assert_eq!(allocator.allocate([0xAB; 16], 42).unwrap(), Some(42));
+    assert_eq!(allocator.allocate([0xCD; 16], 42).unwrap(), Some(43));
+
+    let mut allocator = PnpAllocator::open(&path).unwrap();
+    assert_eq!(allocator.get(&[0xAB; 16]), Some(42));
+    assert_eq!(allocator.allocate([0xCD; 16], 1).unwrap(), Some(43));
+
+    std::fs::write(&path, "not a table\n").unwrap();
+    assert!(matches!(PnpAllocator::open(&path), Err(Error::Storage(_))));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn server_answers_on_runtime() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+    let server = PnpServer::spawn(&runtime, PnpAllocator::new());
+    runtime.interface().driver.push_rx(request([9; 16], 0xFFFF));
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(server.allocator().get(&[9; 16]), Some(125));
+    let transmitted = runtime.interface().driver.take_transmitted();
+    assert_eq!(decode(&transmitted[0]), NodeIdAllocationData { node_id: 125, unique_id: [9; 16] });
+}
+
+#[tokio::test]
+async fn stop_with_server_running() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+    let server = PnpServer::spawn(&runtime, PnpAllocator::new());
+    runtime.interface().driver.push_rx(request([3; 16], 0xFFFF));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let mut interface = tokio::time::timeout(Duration::from_secs(1), runtime.stop()).await.unwrap();
+    assert_eq!(decode(&interface.driver.take_transmitted()[0]).unique_id, [3; 16]);
+    drop(server);
+}
@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant};
+
+use cands_cyphal::backend::{encode_fifo_element, packets_to_fifo, MockBackend, FIFO_ELEMENT_SIZE};
+use cands_cyphal::{CANInterface, CANInterfaceBuilder, CyphalMiddleware, Error, RxStats};
+use cands_transport::cyphal::CyphalTxPacket;
+
+const SUBJECT_ID: u16 = 0x100;
+
+fn interface() -> CANInterface<MockBackend> {
+    CANInterface::with_backend(MockBackend::new()).unwrap()
+}
+
+/// Frames of one multi-frame message transfer.
+fn transfer(source_node_id: u8, transfer_id: u8, payload: &[u8]) -> Vec<CyphalTxPacket<64>> {
+    let mut middleware = CyphalMiddleware::<64>::new(source_node_id);
+    middleware.transfer_id = transfer_id;
+    middleware.create_message_data(SUBJECT_ID, payload, payload.len()).unwrap()
+}
+
+/// Fills three frames exactly, so the received payload carries no padding.
+fn long_payload(seed: u8) -> Vec<u8> {
+    (0..155).map(|x: u8| x.wrapping_mul(seed)).collect()
+}
+
+fn load(interface: &mut CANInterface<MockBackend>, packets: &[&CyphalTxPacket<64>]) -> Result<(), Error> {
+    let packets: Vec<CyphalTxPacket<64>> = packets.iter().map(|&packet| packet.clone()).collect();
+    interface.load_frames_from_buffer(&packets_to_fifo(&packets))
+}
+
+fn received_payloads(interface: &CANInterface<MockBackend>) -> Vec<(u8, Vec<u8>)> {
+    interface.rx_complete_fifo
+        .iter()
+        .map(|frame| (frame.props.source_node_id, frame.payload[..frame.payload_size].to_vec()))
+        .collect()
+}
+
+#[test]
+fn interleaved_sources_are_kept_apart() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+    let b = transfer(4, 1, &long_payload(5));
+    assert_eq!(a.len(), 3);
+
+    load(&mut interface, &[&a[0], &b[0], &a[1], &b[1], &b[2], &a[2]]).unwrap();
+
+    let received = received_payloads(&interface);
+    assert_eq!(received.len(), 2);
+    assert!(received.contains(&(3, long_payload(3))));
+    assert!(received.contains(&(4, long_payload(5))));
+    assert_eq!(interface.rx_stats, RxStats::default());
+}
+
+#[test]
+fn interleaved_transfer_ids_are_kept_apart() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+    let b = transfer(3, 2, &long_payload(5));
+
+    load(&mut interface, &[&a[0], &b[0], &b[1], &a[1], &a[2], &b[2]]).unwrap();
+
+    assert_eq!(received_payloads(&interface), vec![(3, long_payload(3)), (3, long_payload(5))]);
+}
+
+#[test]
+fn duplicate_frames_are_dropped() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+
+    load(&mut interface, &[&a[0], &a[0], &a[1], &a[1], &a[2]]).unwrap();
+
+    assert_eq!(received_payloads(&interface), vec![(3, long_payload(3))]);
+    assert_eq!(interface.rx_stats.duplicate_frames, 2);
+}
+
+#[test]
+fn missing_frame_is_a_toggle_error() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+
+    load(&mut interface, &[&a[0], &a[2]]).unwrap();
+
+    assert!(interface.rx_complete_fifo.is_empty());
+    assert!(interface.rx_incomplete_fifo.is_empty());
+    assert_eq!(interface.rx_stats.toggle_errors, 1);
+}
+
+#[test]
+fn frames_without_start_are_orphans() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+
+    load(&mut interface, &[&a[1], &a[2]]).unwrap();
+
+    assert!(interface.rx_complete_fifo.is_empty());
+    assert_eq!(interface.rx_stats.orphan_frames, 2);
+}
+
+#[test]
+fn restarted_transfer_replaces_incomplete_one() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+    let b = transfer(3, 1, &long_payload(5));
+
+    load(&mut interface, &[&a[0], &a[1], &b[0], &b[1], &b[2]]).unwrap();
+
+    assert_eq!(received_payloads(&interface), vec![(3, long_payload(5))]);
+    assert_eq!(interface.rx_stats.interrupted_transfers, 1);
+}
+
+#[test]
+fn crc_errors_are_counted() {
+    let mut interface = interface();
+    let mut a = transfer(3, 1, &long_payload(3));
+    a[1].payload[0] ^= 0xFF;
+
+    let b = transfer(4, 1, &long_payload(5));
+
+    // The corrupt transfer must not hold back the one after it in the same buffer.
+    load(&mut interface, &[&a[0], &a[1], &a[2], &b[0], &b[1], &b[2]]).unwrap();
+    assert_eq!(interface.rx_stats.crc_errors, 1);
+    assert_eq!(received_payloads(&interface), vec![(4, long_payload(5))]);
+    assert!(interface.rx_incomplete_fifo.is_empty());
+
+    interface.reset_rx_stats();
+    assert_eq!(interface.rx_stats, RxStats::default());
+}
+
+#[test]
+fn stale_transfers_are_evicted_after_timeout() {
+    let mut interface = CANInterfaceBuilder::new()
+        .transfer_id_timeout(Duration::from_millis(100))
+        .build(MockBackend::new())
+        .unwrap();
+    let a = transfer(3, 1, &long_payload(3));
+    let b = transfer(4, 1, &long_payload(5));
+    let t0 = Instant::now();
+
+    let packets: Vec<CyphalTxPacket<64>> = vec![a[0].clone(), a[1].clone()];
+    interface.load_frames_from_buffer_at(&packets_to_fifo(&packets), t0).unwrap();
+    assert_eq!(interface.rx_incomplete_fifo.len(), 1);
+    assert_eq!(interface.rx_incomplete_fifo[0].timestamp, t0);
+
+    interface.load_frames_from_buffer_at(&packets_to_fifo(&b), t0 + Duration::from_millis(150)).unwrap();
+    assert!(interface.rx_incomplete_fifo.is_empty());
+    assert_eq!(interface.rx_stats.timed_out_transfers, 1);
+    assert_eq!(received_payloads(&interface), vec![(4, long_payload(5))]);
+
+    // The end frame of the evicted transfer no longer has a session to join.
+    interface.load_frames_from_buffer_at(&packets_to_fifo(&a[2..]), t0 + Duration::from_millis(160)).unwrap();
+    assert_eq!(interface.rx_stats.orphan_frames, 1);
+}
+
+#[test]
+fn concurrent_reassemblies_are_capped() {
+    let mut interface = CANInterfaceBuilder::new()
+        .max_incomplete_transfers(2)
+        .build(MockBackend::new())
+        .unwrap();
+    let starts: Vec<CyphalTxPacket<64>> = (0..3).map(|transfer_id| transfer(3, transfer_id, &long_payload(3)).remove(0)).collect();
+
+    interface.load_frames_from_buffer(&packets_to_fifo(&starts)).unwrap();
+
+    assert_eq!(interface.rx_incomplete_fifo.len(), 2);
+    assert_eq!(interface.rx_incomplete_fifo[0].frame.props.transfer_id, 1);
+    assert_eq!(interface.rx_stats.evicted_transfers, 1);
+
+    interface.max_incomplete_transfers = 0;
+    interface.load_frames_from_buffer(&packets_to_fifo(&starts[..1])).unwrap();
+    assert_eq!(interface.rx_incomplete_fifo.len(), 1);
+}
+
+#[test]
+fn completed_transfer_carries_first_frame_timestamp() {
+    let mut interface = interface();
+    let a = transfer(3, 1, &long_payload(3));
+    let single = transfer(4, 1, &[1, 2, 3]);
+    let t0 = Instant::now();
+    let t1 = t0 + Duration::from_millis(5);
+
+    interface.load_frames_from_buffer_at(&packets_to_fifo(&a[..2]), t0).unwrap();
+    let mut packets: Vec<CyphalTxPacket<64>> = a[2..].to_vec();
+    packets.extend(single);
+    interface.load_frames_from_buffer_at(&packets_to_fifo(&packets), t1).unwrap();
+
+    assert_eq!(interface.rx_complete_fifo.len(), 2);
+    assert_eq!(interface.rx_complete_fifo[0].props.source_node_id, 3);
+    assert_eq!(interface.rx_complete_fifo[0].timestamp, t0);
+    assert_eq!(interface.rx_complete_fifo[1].props.source_node_id, 4);
+    assert_eq!(interface.rx_complete_fifo[1].timestamp, t1);
+}
+
+#[test]
+fn fifo_element_rejects_oversized_payload() {
+    let xid: u32 = transfer(3, 1, &[1, 2, 3])[0].xid;
+
+    let element = encode_fifo_element(xid, &[0xE1; 64]).unwrap();
+    assert_eq!(element.len(), FIFO_ELEMENT_SIZE);
+    let err = encode_fifo_element(xid, &[0xE1; 65]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
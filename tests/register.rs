@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::uavcan::{Register, RegisterAccessRequest, Value, REGISTER_ACCESS_SERVICE_ID, REGISTER_LIST_SERVICE_ID};
+use cands_cyphal::{CANInterface, Error};
+
+mod common;
+
+const NODE_ID: u8 = 10;
+const TIMEOUT: Duration = Duration::from_millis(20);
+
+/// A node exposing a few registers; `uavcan.node.description` is the only mutable one.
+fn node() -> impl FnMut(&TxFrame) -> Vec<Vec<u8>> + Send + 'static {
+    let mut registers: Vec<(String, Register)> = vec![
+        ("uavcan.node.id".into(), Register { timestamp: 0, mutable: false, persistent: true, value: Value::Natural16(vec![NODE_ID as u16]) }),
+        ("uavcan.node.description".into(), Register { timestamp: 0, mutable: true, persistent: true, value: Value::String("drive".into()) }),
+        ("drive.gains".into(), Register { timestamp: 1234, mutable: false, persistent: false, value: Value::Real32(vec![0.5; 20]) }),
+    ];
+
+    move |frame: &TxFrame| {
+        let packet = common::decode(frame);
+        if packet.props.destination_node_id != NODE_ID {
+            return vec![];
+        }
+        let request: &[u8] = &packet.payload[..packet.payload_size];
+
+        let payload: Vec<u8> = match packet.props.port_id {
+            REGISTER_ACCESS_SERVICE_ID => {
+                let request = RegisterAccessRequest::deserialize(request).unwrap();
+                match registers.iter_mut().find(|(name, _)| *name == request.name) {
+                    Some((_, register)) => {
+                        if register.mutable & !request.value.is_empty() {
+                            register.value = request.value;
+                        }
+                        register.serialize()
+                    },
+                    None => Register::default().serialize()
+                }
+            },
+            REGISTER_LIST_SERVICE_ID => {
+                let index: usize = u16::from_le_bytes([request[0], request[1]]) as usize;
+                let name: &str = registers.get(index).map(|(name, _)| name.as_str()).unwrap_or("");
+                [&[name.len() as u8], name.as_bytes()].concat()
+            },
+            _ => return vec![]
+        };
+
+        vec![common::respond(&packet, &payload)]
+    }
+}
+
+fn interface() -> CANInterface<MockBackend> {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    interface.driver.set_responder(node());
+    interface
+}
+
+#[test]
+fn value_round_trip() {
+    let values = [
+        Value::Empty,
+        Value::String("uavcan.node.id".into()),
+        Value::Unstructured(vec![0xAA; 256]),
+        Value::Bit(vec![true, false, true, true, false, false, false, false, true]),
+        Value::Integer64(vec![-1, i64::MAX]),
+        Value::Integer8(vec![-5; 256]),
+        Value::Natural32(vec![7, 8, 9]),
+        Value::Real64(vec![1.25]),
+        Value::Real16(vec![1.0, -2.5, 65504.0, 0.0]),
+    ];
+    for value in values {
+        assert_eq!(Value::deserialize(&value.serialize()).unwrap(), value);
+    }
+
+    assert_eq!(Value::Bit(vec![true, false, true]).serialize(), vec![3, 3, 0, 0b101]);
+    assert_eq!(Value::Natural16(vec![0x0102]).serialize(), vec![10, 1, 0x02, 0x01]);
+    assert_eq!(Value::Real16(vec![1.0]).serialize(), vec![14, 1, 0x00, 0x3C]);
+    assert!(Value::deserialize(&[15]).is_err());
+    assert!(Value::deserialize(&[4, 33]).is_err());
+}
+
+#[test]
+fn register_round_trip() {
+    let register = Register { timestamp: 0x00AB_CDEF_0123_4567, mutable: true, persistent: false, value: Value::Integer32(vec![-3]) };
+    let bytes = register.serialize();
+    assert_eq!(&bytes[..8], &[0x67, 0x45, 0x23, 0x01, 0xEF, 0xCD, 0xAB, 0x01]);
+    assert_eq!(Register::deserialize(&bytes).unwrap(), register);
+}
+
+#[test]
+fn read_and_write_register() {
+    let mut interface = interface();
+
+    let register = interface.read_register(NODE_ID, "uavcan.node.id", TIMEOUT).unwrap().unwrap();
+    assert_eq!(register.value, Value::Natural16(vec![NODE_ID as u16]));
+    assert!(!register.mutable & register.persistent);
+
+    let register = interface.access_register(NODE_ID, "uavcan.node.description", Value::String("left wheel".into()), TIMEOUT).unwrap().unwrap();
+    assert_eq!(register.value, Value::String("left wheel".into()));
+
+    let register = interface.access_register(NODE_ID, "uavcan.node.id", Value::Natural16(vec![3]), TIMEOUT).unwrap().unwrap();
+    assert_eq!(register.value, Value::Natural16(vec![NODE_ID as u16]));
+
+    assert!(interface.read_register(NODE_ID, "missing", TIMEOUT).unwrap().unwrap().value.is_empty());
+    assert_eq!(interface.read_register(NODE_ID + 1, "uavcan.node.id", TIMEOUT).unwrap(), None);
+}
+
+#[test]
+fn dump_registers_enumerates_all() {
+    let mut interface = interface();
+
+    let dump = interface.dump_registers(NODE_ID, TIMEOUT).unwrap().unwrap();
+    let names: Vec<&str> = dump.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["uavcan.node.id", "uavcan.node.description", "drive.gains"]);
+    assert_eq!(dump[2].1.timestamp, 1234);
+    assert_eq!(dump[2].1.value, Value::Real32(vec![0.5; 20]));
+
+    assert_eq!(interface.dump_registers(NODE_ID + 1, TIMEOUT).unwrap(), None);
+}
+
+#[test]
+fn list_fails_if_node_stops_answering() {
+    let mut interface = interface();
+    let mut node = node();
+    let mut requests: usize = 0;
+    interface.driver.set_responder(move |frame: &TxFrame| {
+        requests += 1;
+        match requests <= 2 {
+            true => node(frame),
+            false => vec![]
+        }
+    });
+
+    let err = interface.list_registers(NODE_ID, TIMEOUT).err().unwrap();
+    assert!(matches!(err, Error::Timeout { channel: NODE_ID, .. }));
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::{CANInterface, CANRuntime, Error};
+use futures_lite::StreamExt;
+
+mod common;
+use common::message;
+
+const SUBJECT_ID: u16 = 0x100;
+const RECV_TIMEOUT: Duration = Duration::from_millis(500);
+
+fn runtime() -> CANRuntime<MockBackend> {
+    CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap())
+}
+
+#[tokio::test]
+async fn subscribers_share_frames_on_their_port() {
+    let runtime = runtime();
+    let mut first = runtime.subscribe(SUBJECT_ID);
+    let mut second = runtime.subscribe(SUBJECT_ID);
+    let mut other = runtime.subscribe(SUBJECT_ID + 1);
+
+    runtime.interface().driver.push_rx(message(3, SUBJECT_ID, &[1, 2, 3]));
+
+    for subscription in [&mut first, &mut second] {
+        let frame = tokio::time::timeout(RECV_TIMEOUT, subscription.recv()).await.unwrap().unwrap();
+        assert_eq!(frame.props.source_node_id, 3);
+        assert_eq!(&frame.payload[..3], &[1, 2, 3]);
+    }
+    assert!(tokio::time::timeout(Duration::from_millis(20), other.recv()).await.is_err());
+}
+
+#[tokio::test]
+async fn subscription_as_stream() {
+    let runtime = runtime();
+    let mut stream = Box::pin(runtime.subscribe(SUBJECT_ID).into_stream());
+
+    runtime.interface().driver.push_rx(message(3, SUBJECT_ID, &[1]));
+    runtime.interface().driver.push_rx(message(4, SUBJECT_ID, &[2]));
+
+    let sources: Vec<u8> = tokio::time::timeout(RECV_TIMEOUT, stream.as_mut().take(2).map(|frame| frame.props.source_node_id).collect())
+        .await
+        .unwrap();
+    assert_eq!(sources, vec![3, 4]);
+}
+
+#[tokio::test]
+async fn stop_returns_interface_and_closes_subscriptions() {
+    let runtime = runtime();
+    let mut subscription = runtime.subscribe(SUBJECT_ID);
+
+    let interface = runtime.stop().await;
+    assert_eq!(interface.driver.setup_count, 1);
+    assert!(matches!(subscription.recv().await, Err(Error::Closed)));
+}
+
+#[test]
+fn spawn_outside_tokio_runtime() {
+    let runtime = runtime();
+    runtime.interface().driver.push_rx(message(3, SUBJECT_ID, &[1]));
+
+    let tokio = tokio::runtime::Runtime::new().unwrap();
+    let interface = tokio.block_on(runtime.stop());
+    assert_eq!(interface.driver.setup_count, 1);
+}
+
+#[tokio::test]
+async fn reader_keeps_running_after_backend_error() {
+    let runtime = runtime();
+    let mut subscription = runtime.subscribe(SUBJECT_ID);
+
+    runtime.interface().driver.receive_error = Some(std::io::ErrorKind::BrokenPipe);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(matches!(runtime.take_reader_error(), Some(Error::Driver(err)) if err.kind() == std::io::ErrorKind::BrokenPipe));
+    assert!(runtime.take_reader_error().is_none());
+
+    runtime.interface().driver.push_rx(message(3, SUBJECT_ID, &[1]));
+    let frame = tokio::time::timeout(RECV_TIMEOUT, subscription.recv()).await.unwrap().unwrap();
+    assert_eq!(frame.props.source_node_id, 3);
+}
+
+#[cfg(feature="drvcan_v2")]
+#[tokio::test]
+async fn subscribe_digitalservo_filters_by_key_and_node() {
+    use cands_cyphal::serde::digitalservo::dictionary::{Dict, DigitalServoPrimitiveData};
+
+    const VALUE_SUBJECT_ID: u16 = 0x488;
+
+    let runtime = runtime();
+    let mut subscription = runtime.subscribe_digitalservo(Some("cmdval"), Some(3));
+
+    let mut buffer = message(4, VALUE_SUBJECT_ID, &Dict::serialize("cmdval", &[1.0]));
+    buffer.extend(message(3, VALUE_SUBJECT_ID, &Dict::serialize("drive", &[true])));
+    buffer.extend(message(3, VALUE_SUBJECT_ID, &Dict::serialize("cmdval", &[2.0])));
+    runtime.interface().driver.push_rx(buffer);
+
+    let data = tokio::time::timeout(RECV_TIMEOUT, subscription.recv()).await.unwrap().unwrap();
+    assert_eq!(data.props.source_node_id, 3);
+    assert_eq!(data.data.value, [DigitalServoPrimitiveData::F64(2.0)]);
+}
+
+#[cfg(feature="drvcan_v2")]
+mod digitalservo {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use cands_cyphal::backend::{MockBackend, SimBackend, SimulatedDrive};
+    use cands_cyphal::serde::digitalservo::dictionary::DigitalServoPrimitiveData;
+    use cands_cyphal::{CANInterfaceBuilder, CANRuntime, Error};
+
+    use super::common;
+
+    const AXES: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    const LATENCY: Duration = Duration::from_millis(20);
+
+    fn sim_runtime() -> Arc<CANRuntime<SimBackend>> {
+        let backend = AXES.iter().fold(SimBackend::new().with_latency(LATENCY), |backend, &node_id| {
+            backend.with_drive(SimulatedDrive::new(node_id).with_value("cmdval", &[node_id as f64]))
+        });
+        let interface = CANInterfaceBuilder::new()
+            .timeout(Duration::from_millis(200))
+            .retry_count(2)
+            .build(backend)
+            .unwrap();
+        Arc::new(CANRuntime::spawn(interface))
+    }
+
+    #[tokio::test]
+    async fn requests_to_several_drives_run_concurrently() {
+        let runtime = sim_runtime();
+        let start = Instant::now();
+
+        let tasks: Vec<_> = AXES.iter().map(|&node_id| {
+            let runtime = runtime.clone();
+            tokio::spawn(async move { runtime.send_digitalservo_get_value(node_id, "cmdval").await })
+        }).collect();
+
+        for (task, node_id) in tasks.into_iter().zip(AXES) {
+            let ret = task.await.unwrap().unwrap();
+            assert_eq!(ret.props.source_node_id, node_id);
+            assert_eq!(ret.data.value, [DigitalServoPrimitiveData::F64(node_id as f64)]);
+        }
+        assert!(start.elapsed() < LATENCY * 4);
+    }
+
+    #[tokio::test]
+    async fn concurrent_set_values() {
+        let runtime = sim_runtime();
+
+        let tasks: Vec<_> = AXES.iter().map(|&node_id| {
+            let runtime = runtime.clone();
+            tokio::spawn(async move { runtime.send_digitalservo_set_value(node_id, "cmdval", &[10.0 + node_id as f64]).await })
+        }).collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let interface = runtime.interface();
+        for node_id in AXES {
+            assert_eq!(interface.driver.drive(node_id).unwrap().get("cmdval"), Some(&[DigitalServoPrimitiveData::F64(10.0 + node_id as f64)][..]));
+        }
+    }
+
+    #[tokio::test]
+    async fn wrapped_transfer_id_is_not_reused_while_pending() {
+        let runtime = Arc::new(CANRuntime::spawn(CANInterfaceBuilder::new().build(MockBackend::new()).unwrap()));
+
+        // Transfer IDs wrap at 32, so the 33rd request to a silent node collides with the first.
+        let tasks: Vec<_> = (0..33).map(|_| {
+            let runtime = runtime.clone();
+            tokio::spawn(async move { runtime.request(0x82, 3, &[], Duration::from_millis(200)).await })
+        }).collect();
+
+        let mut in_use: usize = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(None) => {},
+                Err(Error::TransferIdInUse { channel: 3, .. }) => in_use += 1,
+                ret => panic!("unexpected {:?}", ret.map(|frame| frame.is_some())),
+            }
+        }
+        assert_eq!(in_use, 1);
+        assert_eq!(runtime.interface().driver.transmitted.len(), 32);
+    }
+
+    #[tokio::test]
+    async fn response_with_other_transfer_id_is_ignored() {
+        let mut backend = MockBackend::new();
+        backend.set_responder(|frame| {
+            let request = common::decode(frame);
+            vec![common::response(3, 0x87, request.props.transfer_id.wrapping_add(1), &[0])]
+        });
+        let interface = CANInterfaceBuilder::new()
+            .timeout(Duration::from_millis(20))
+            .retry_count(2)
+            .build(backend)
+            .unwrap();
+        let runtime = CANRuntime::spawn(interface);
+
+        let err = runtime.send_digitalservo_set_value(3, "cmdval", &[1.0]).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { channel: 3, attempts: 2, .. }));
+    }
+}
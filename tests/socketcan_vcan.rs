@@ -0,0 +1,87 @@
+#![cfg(all(feature="socketcan", feature="drvcan_v2"))]
+
+//! Runs against a CAN FD capable virtual interface:
+//! ```sh
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set vcan0 mtu 72 up
+//! cargo test --features socketcan,drvcan_v2 -- --ignored
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cands_cyphal::backend::{CANBackend, SimulatedDrive, SocketCANBackend, TxFrame, FIFO_ELEMENT_SIZE};
+use cands_cyphal::serde::digitalservo::dictionary::DigitalServoPrimitiveData;
+use cands_cyphal::CANInterface;
+
+const INTERFACE: &str = "vcan0";
+const DRIVE_NODE_ID: u8 = 5;
+
+/// Serve a simulated drive on the interface until `stop` is set.
+fn spawn_drive(stop: Arc<AtomicBool>) -> std::thread::JoinHandle<SimulatedDrive> {
+    let mut backend = SocketCANBackend::open(INTERFACE).unwrap();
+    backend.setup(&[], &[]).unwrap();
+
+    std::thread::spawn(move || {
+        let mut drive = SimulatedDrive::new(DRIVE_NODE_ID);
+        while !stop.load(Ordering::Relaxed) {
+            let rx_data = match backend.receive().unwrap() {
+                Some(rx_data) => rx_data,
+                None => {
+                    std::thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+            };
+            for element in rx_data.fifo1.chunks(FIFO_ELEMENT_SIZE) {
+                let xid = u32::from_le_bytes([element[0], element[1], element[2], element[3]]);
+                let dlc = (element[6] & 0x0F) as usize;
+                let len = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64][dlc];
+                let frame = TxFrame { xid, payload: element[8..8 + len].to_vec() };
+                for packet in drive.handle(&frame) {
+                    backend.transmit(packet.xid, &packet.payload, packet.payload_size).unwrap();
+                }
+            }
+        }
+        drive
+    })
+}
+
+#[test]
+#[ignore = "requires a CAN FD capable vcan0 interface"]
+fn request_sync_over_vcan() {
+    let stop = Arc::new(AtomicBool::new(false));
+    let drive = spawn_drive(stop.clone());
+
+    let mut interface = CANInterface::with_backend(SocketCANBackend::open(INTERFACE).unwrap()).unwrap();
+    interface.set_timeout(Duration::from_millis(50));
+
+    interface.drive_enable(DRIVE_NODE_ID).unwrap();
+    interface.send_cmdval(DRIVE_NODE_ID, 2.5).unwrap();
+    assert_eq!(interface.get_scalar_response::<f64>(DRIVE_NODE_ID, "cmdval").unwrap(), Some(2.5));
+
+    let value: Vec<f64> = (0..16).map(|x| x as f64).collect();
+    interface.send_cmdarray(DRIVE_NODE_ID, &value).unwrap();
+    assert_eq!(interface.get_vector_response(DRIVE_NODE_ID, "cmdarray").unwrap().len(), 16);
+
+    stop.store(true, Ordering::Relaxed);
+    let drive = drive.join().unwrap();
+    assert_eq!(drive.get("cmdval"), Some(&[DigitalServoPrimitiveData::F64(2.5)][..]));
+}
+
+#[test]
+#[ignore = "requires a CAN FD capable vcan0 interface"]
+fn messages_loop_between_sockets() {
+    let mut rx = SocketCANBackend::open(INTERFACE).unwrap();
+    rx.setup(&[], &[]).unwrap();
+    let mut tx = CANInterface::with_backend(SocketCANBackend::open(INTERFACE).unwrap()).unwrap();
+
+    let payload: Vec<u8> = (0..100).collect();
+    tx.send_message(0x100, &payload).unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+
+    let mut listener = CANInterface::with_backend(rx).unwrap();
+    listener.load_frames().unwrap();
+    assert_eq!(listener.rx_complete_fifo.len(), 1);
+    assert_eq!(&listener.rx_complete_fifo[0].payload[..payload.len()], &payload[..]);
+}
@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+
+use cands_cyphal::backend::{packets_to_fifo, MockBackend, TxFrame};
+use cands_cyphal::uavcan::{synchronized_time, HeartbeatPublisher, Synchronization, TimeSyncEstimator, TimeSyncPublisher, HEARTBEAT_SUBJECT_ID, TIME_SYNC_SUBJECT_ID};
+use cands_cyphal::{CANInterface, CANRuntime, CyphalMiddleware};
+
+mod common;
+
+fn decode(frame: &TxFrame) -> Synchronization {
+    let packet = common::decode(frame);
+    assert_eq!(packet.props.port_id, TIME_SYNC_SUBJECT_ID);
+    Synchronization::deserialize(&packet.payload[..packet.payload_size]).unwrap()
+}
+
+fn message(previous: u64) -> Synchronization {
+    Synchronization { previous_transmission_timestamp_microseconds: previous }
+}
+
+#[test]
+fn synchronization_round_trip() {
+    let bytes = message(0x00AB_CDEF_0123_4567).serialize();
+    assert_eq!(bytes, vec![0x67, 0x45, 0x23, 0x01, 0xEF, 0xCD, 0xAB]);
+    assert_eq!(Synchronization::deserialize(&bytes).unwrap(), message(0x00AB_CDEF_0123_4567));
+}
+
+#[test]
+fn publisher_reports_previous_transmit_timestamp() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    let mut publisher = TimeSyncPublisher::new().with_period(Duration::from_millis(5));
+
+    assert!(publisher.tick(&mut interface).unwrap());
+    let first_transmit = interface.driver.last_transmit.unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+    assert!(publisher.tick(&mut interface).unwrap());
+
+    let transmitted = interface.driver.take_transmitted();
+    assert_eq!(decode(&transmitted[0]), message(0));
+    let reported = decode(&transmitted[1]).previous_transmission_timestamp_microseconds;
+    assert!(reported.abs_diff(synchronized_time(first_transmit)) < 1000);
+}
+
+#[test]
+fn estimator_maps_local_time_to_master_time() {
+    let mut estimator = TimeSyncEstimator::new();
+    let t0 = Instant::now();
+
+    assert!(!estimator.update(5, 30, &message(0), t0));
+    assert_eq!(estimator.master_time(t0), None);
+    assert!(estimator.update(5, 31, &message(1_000_000), t0 + Duration::from_secs(1)));
+    assert_eq!(estimator.master_time(t0 + Duration::from_millis(500)), Some(1_500_000));
+
+    // Transfer IDs wrap; a gap breaks the measurement chain.
+    assert!(estimator.update(5, 0, &message(2_000_000), t0 + Duration::from_secs(2)));
+    assert!(!estimator.update(5, 2, &message(3_000_000), t0 + Duration::from_secs(3)));
+    assert_eq!(estimator.master_time(t0 + Duration::from_secs(1)), Some(2_000_000));
+}
+
+#[test]
+fn estimator_follows_lowest_master() {
+    let mut estimator = TimeSyncEstimator::new();
+    let t0 = Instant::now();
+
+    estimator.update(9, 0, &message(0), t0);
+    estimator.update(4, 0, &message(0), t0 + Duration::from_millis(10));
+    assert!(!estimator.update(9, 1, &message(7), t0 + Duration::from_millis(20)));
+    assert_eq!(estimator.master(), Some(4));
+    assert!(estimator.update(4, 1, &message(42), t0 + Duration::from_millis(30)));
+    assert_eq!(estimator.master_time(t0 + Duration::from_millis(10)), Some(42));
+
+    // A silent master is replaced.
+    assert!(!estimator.update(9, 2, &message(7), t0 + Duration::from_secs(3)));
+    assert_eq!(estimator.master(), Some(9));
+}
+
+#[test]
+fn poll_takes_messages_from_interface() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    let mut master = CyphalMiddleware::<64>::new(3);
+    let mut fifo = vec![];
+    for previous in [0, 123_456] {
+        let payload = message(previous).serialize();
+        fifo.extend(packets_to_fifo(&master.create_message_data(TIME_SYNC_SUBJECT_ID, &payload, payload.len()).unwrap()));
+    }
+    interface.driver.push_rx(fifo);
+
+    let mut estimator = TimeSyncEstimator::new();
+    assert!(estimator.poll(&mut interface).unwrap());
+    assert_eq!(estimator.master(), Some(3));
+    assert!(estimator.offset().is_some());
+    assert!(interface.rx_complete_fifo.is_empty());
+}
+
+#[tokio::test]
+async fn runtime_drives_time_sync() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+
+    runtime.start_time_sync(TimeSyncPublisher::new().with_period(Duration::from_millis(10)));
+    tokio::time::sleep(Duration::from_millis(35)).await;
+    assert!(runtime.stop_time_sync().is_some());
+
+    let transmitted = runtime.interface().driver.take_transmitted();
+    assert!(transmitted.len() >= 3);
+    assert!(decode(&transmitted[2]).previous_transmission_timestamp_microseconds > decode(&transmitted[1]).previous_transmission_timestamp_microseconds);
+}
+
+#[tokio::test]
+async fn time_sync_stays_consecutive_beside_other_traffic() {
+    let runtime = CANRuntime::spawn(CANInterface::with_backend(MockBackend::new()).unwrap());
+
+    runtime.start_heartbeat(HeartbeatPublisher::new().with_period(Duration::from_millis(10)));
+    runtime.start_time_sync(TimeSyncPublisher::new().with_period(Duration::from_millis(10)));
+    for _ in 0..4 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        runtime.interface().send_request(0x80, 1, &[0]).unwrap();
+    }
+    assert!(runtime.stop_time_sync().is_some());
+    assert!(runtime.stop_heartbeat().is_some());
+
+    let packets: Vec<_> = runtime.interface().driver.take_transmitted().iter().map(common::decode).collect();
+    for subject_id in [HEARTBEAT_SUBJECT_ID, TIME_SYNC_SUBJECT_ID] {
+        let transfer_ids: Vec<u8> = packets.iter().filter(|packet| packet.props.port_id == subject_id).map(|packet| packet.props.transfer_id).collect();
+        assert!(transfer_ids.len() >= 3);
+        assert!(transfer_ids.windows(2).all(|pair| pair[1] == (pair[0] + 1) & 31));
+    }
+
+    let mut estimator = TimeSyncEstimator::new();
+    let t0 = Instant::now();
+    let synchronized: Vec<bool> = packets.iter()
+        .filter(|packet| packet.props.port_id == TIME_SYNC_SUBJECT_ID)
+        .enumerate()
+        .map(|(i, packet)| {
+            let message = Synchronization::deserialize(&packet.payload[..packet.payload_size]).unwrap();
+            estimator.update(packet.props.source_node_id, packet.props.transfer_id, &message, t0 + Duration::from_millis(10 * i as u64))
+        })
+        .collect();
+    assert!(!synchronized[0]);
+    assert!(synchronized[1..].iter().all(|&ok| ok));
+    assert!(estimator.offset().is_some());
+}
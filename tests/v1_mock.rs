@@ -0,0 +1,35 @@
+#![cfg(feature="drvcan_v1")]
+
+use cands_cyphal::backend::MockBackend;
+use cands_cyphal::serde::digitalservo::dictionary::{DigitalServoPrimitiveData, Dict};
+use cands_cyphal::CANInterface;
+
+mod common;
+use common::message;
+
+const DRIVE_NODE_ID: u8 = 3;
+
+#[test]
+fn message_is_broadcast_as_dict() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+
+    interface.send_digitalservo_message("cmdval", &[1.5]).unwrap();
+
+    let packet = common::decode(&interface.driver.take_transmitted()[0]);
+    assert_eq!(packet.props.port_id, 0x488);
+    let dict = Dict::deserialize(&packet.payload[..packet.payload_size]).unwrap();
+    assert_eq!((dict.key.as_str(), dict.value), ("cmdval", vec![DigitalServoPrimitiveData::F64(1.5)]));
+}
+
+#[test]
+fn get_key_value_takes_dicts() {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    let mut buffer = message(DRIVE_NODE_ID, 0x488, &Dict::serialize("drive", &[true]));
+    buffer.extend(message(DRIVE_NODE_ID, 0x100, &[1]));
+    interface.driver.push_rx(buffer);
+
+    let data = interface.get_key_value().unwrap().unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].data.key, "drive");
+    assert_eq!(interface.rx_complete_fifo.len(), 1);
+}
@@ -0,0 +1,349 @@
+#![cfg(feature="drvcan_v2")]
+
+use std::time::{Duration, Instant};
+
+use cands_cyphal::backend::{MockBackend, TxFrame};
+use cands_cyphal::digitalservo::{DriveState, ErrorCode, GeneralStatus, ResultCode};
+use cands_cyphal::serde::digitalservo::{dictionary::Dict, string::Str};
+use cands_cyphal::{CANInterface, Error};
+
+mod common;
+use common::{message, response};
+
+const DRIVE_NODE_ID: u8 = 3;
+
+// Ports a drive answers on.
+const RESULT_PORT_ID: u16 = 0x87;
+const VALUE_PORT_ID: u16 = 0x80;
+
+fn interface() -> CANInterface<MockBackend> {
+    let mut interface = CANInterface::with_backend(MockBackend::new()).unwrap();
+    interface.set_timeout(Duration::from_millis(10));
+    interface.set_retry_count(3);
+    interface
+}
+
+/// Port, destination, transfer ID and payload of a transmitted request.
+fn decode(frame: &TxFrame) -> (u16, u8, u8, Vec<u8>) {
+    let packet = common::decode(frame);
+    (packet.props.port_id, packet.props.destination_node_id, packet.props.transfer_id, packet.payload[..packet.payload_size].to_vec())
+}
+
+#[test]
+fn init_sets_up_backend() {
+    let interface = interface();
+    assert_eq!(interface.driver.setup_count, 1);
+    assert!(interface.middleware.transfer_id < 32);
+}
+
+#[test]
+fn set_value_succeeds_on_zero_result() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[0])]
+    });
+
+    interface.send_digitalservo_set_value(DRIVE_NODE_ID, "cmdval", &[1.5]).unwrap();
+
+    let transmitted = interface.driver.take_transmitted();
+    assert_eq!(transmitted.len(), 1);
+    let (port_id, destination, _, payload) = decode(&transmitted[0]);
+    assert_eq!(port_id, 0x81);
+    assert_eq!(destination, DRIVE_NODE_ID);
+    assert_eq!(&payload[..Dict::serialize("cmdval", &[1.5]).len()], &Dict::serialize("cmdval", &[1.5])[..]);
+}
+
+#[test]
+fn set_value_retries_then_times_out() {
+    let mut interface = interface();
+
+    let err = interface.send_digitalservo_set_value(DRIVE_NODE_ID, "cmdval", &[0.0]).unwrap_err();
+
+    assert!(matches!(err, Error::Timeout { channel: DRIVE_NODE_ID, ref key, attempts: 3 } if key == "cmdval"));
+    assert_eq!(interface.driver.transmitted.len(), 3);
+}
+
+#[test]
+fn set_value_ignores_result_from_other_node() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID + 1, RESULT_PORT_ID, transfer_id, &[0])]
+    });
+
+    assert!(interface.send_digitalservo_set_value(DRIVE_NODE_ID, "cmdval", &[0.0]).is_err());
+}
+
+#[test]
+fn set_value_reports_non_zero_result() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[4])]
+    });
+
+    let err = interface.send_digitalservo_set_value(DRIVE_NODE_ID, "cmdval", &[0.0]).unwrap_err();
+
+    assert!(matches!(err, Error::DriveResult { channel: DRIVE_NODE_ID, code: ResultCode::Other(4), .. }));
+    assert_eq!(interface.driver.transmitted.len(), 1);
+}
+
+#[test]
+fn set_value_recovers_after_lost_reply() {
+    let mut interface = interface();
+    let mut count = 0;
+    interface.driver.set_responder(move |frame| {
+        count += 1;
+        let (_, _, transfer_id, _) = decode(frame);
+        match count {
+            1 => vec![],
+            _ => vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[0])]
+        }
+    });
+
+    interface.send_digitalservo_set_value(DRIVE_NODE_ID, "drive", &[true]).unwrap();
+    assert_eq!(interface.driver.transmitted.len(), 2);
+}
+
+#[test]
+fn get_value_returns_drive_reply() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (port_id, _, transfer_id, payload) = decode(frame);
+        assert_eq!(port_id, 0x82);
+        let key = Str::deserialize(&payload).unwrap().value;
+        vec![response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id, &Dict::serialize(&key, &[42.0]))]
+    });
+
+    let ret = interface.send_digitalservo_get_value(DRIVE_NODE_ID, "cmdval").unwrap();
+
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].data.key, "cmdval");
+    assert_eq!(ret[0].props.source_node_id, DRIVE_NODE_ID);
+    let value: f64 = ret[0].data.value[0].clone().try_into().unwrap();
+    assert_eq!(value, 42.0);
+}
+
+#[test]
+fn get_scalar_response_converts_type() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id, &Dict::serialize("drive", &[true]))]
+    });
+
+    assert_eq!(interface.get_scalar_response::<bool>(DRIVE_NODE_ID, "drive").unwrap(), Some(true));
+    assert!(matches!(interface.get_scalar_response::<f64>(DRIVE_NODE_ID, "drive"), Err(Error::TypeConversion { .. })));
+}
+
+#[test]
+fn get_value_ignores_other_key() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id, &Dict::serialize("cmdval", &[1.0]))]
+    });
+
+    assert!(interface.send_digitalservo_get_value(DRIVE_NODE_ID, "drive").is_err());
+}
+
+#[test]
+fn get_value_matches_reply_by_transfer_id() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![
+            response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id.wrapping_add(1) & 0x1F, &Dict::serialize("cmdval", &[1.0])),
+            response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id, &Dict::serialize("cmdval", &[2.0])),
+        ]
+    });
+
+    let ret = interface.send_digitalservo_get_value(DRIVE_NODE_ID, "cmdval").unwrap();
+    let value: f64 = ret[0].data.value[0].clone().try_into().unwrap();
+    assert_eq!(value, 2.0);
+}
+
+#[test]
+fn get_value_reports_unknown_key() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[1])]
+    });
+
+    let err = interface.send_digitalservo_get_value(DRIVE_NODE_ID, "nokey").unwrap_err();
+    assert!(matches!(err, Error::DriveResult { channel: DRIVE_NODE_ID, .. }));
+    assert_eq!(interface.driver.transmitted.len(), 1);
+}
+
+#[test]
+fn load_frames_reassembles_multi_frame_transfer() {
+    let mut interface = interface();
+    let value: Vec<f64> = (0..16).map(|x| x as f64).collect();
+    let payload = Dict::serialize("cmdarray", &value);
+    interface.driver.push_rx(response(DRIVE_NODE_ID, VALUE_PORT_ID, 0, &payload));
+
+    let ret = interface.get_key_value(Some("cmdarray"), Some(DRIVE_NODE_ID)).unwrap().unwrap();
+
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].data.value.len(), 16);
+    assert!(interface.rx_incomplete_fifo.is_empty());
+}
+
+#[test]
+fn load_frames_drops_crc_error() {
+    let mut interface = interface();
+    let value: Vec<f64> = (0..16).map(|x| x as f64).collect();
+    let mut buffer = response(DRIVE_NODE_ID, VALUE_PORT_ID, 0, &Dict::serialize("cmdarray", &value));
+    buffer[8 + 20] ^= 0xFF;
+
+    interface.load_frames_from_buffer(&buffer).unwrap();
+    assert_eq!(interface.rx_stats.crc_errors, 1);
+    assert!(interface.rx_complete_fifo.is_empty());
+    assert!(interface.rx_incomplete_fifo.is_empty());
+}
+
+#[test]
+fn load_frames_rejects_truncated_buffer() {
+    let mut interface = interface();
+    let buffer = response(DRIVE_NODE_ID, RESULT_PORT_ID, 0, &[0]);
+    assert!(matches!(interface.load_frames_from_buffer(&buffer[..10]), Err(Error::Serialization(_))));
+}
+
+#[test]
+fn get_result_and_get_error_filter_by_node() {
+    let mut interface = interface();
+    let mut buffer = response(DRIVE_NODE_ID, RESULT_PORT_ID, 0, &[0]);
+    buffer.extend(message(DRIVE_NODE_ID, 0x17C0, &[5]));
+    buffer.extend(message(DRIVE_NODE_ID + 1, 0x17C0, &[7]));
+    interface.driver.push_rx(buffer);
+    interface.load_frames().unwrap();
+
+    let errors = interface.get_error(Some(DRIVE_NODE_ID + 1)).unwrap().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].data, ErrorCode::Other(0x07));
+
+    let errors = interface.get_error(None).unwrap().unwrap();
+    assert_eq!(errors[0].data, ErrorCode::Other(0x05));
+
+    let results = interface.get_result(Some(DRIVE_NODE_ID)).unwrap().unwrap();
+    assert_eq!(results[0].data, ResultCode::Ok);
+    assert!(interface.rx_complete_fifo.is_empty());
+}
+
+#[test]
+fn getters_expose_receive_timestamp() {
+    let mut interface = interface();
+    let mut buffer = response(DRIVE_NODE_ID, RESULT_PORT_ID, 0, &[0]);
+    buffer.extend(message(DRIVE_NODE_ID, 0x17C0, &[5]));
+    buffer.extend(response(DRIVE_NODE_ID, VALUE_PORT_ID, 1, &Dict::serialize("cmdval", &[1.0])));
+    interface.driver.push_rx(buffer);
+
+    let before = Instant::now();
+    interface.load_frames().unwrap();
+    let after = Instant::now();
+
+    let results = interface.get_result(None).unwrap().unwrap();
+    let errors = interface.get_error(None).unwrap().unwrap();
+    let values = interface.get_key_value(None, None).unwrap().unwrap();
+    for timestamp in [results[0].timestamp, errors[0].timestamp, values[0].timestamp] {
+        assert!((before <= timestamp) && (timestamp <= after));
+    }
+}
+
+/// Answer set-value with success and get-value of `drive` with `drive`.
+fn drive_responder(drive: bool) -> impl FnMut(&TxFrame) -> Vec<Vec<u8>> + Send + 'static {
+    move |frame| {
+        let (port_id, _, transfer_id, _) = decode(frame);
+        match port_id {
+            0x82 => vec![response(DRIVE_NODE_ID, VALUE_PORT_ID, transfer_id, &Dict::serialize("drive", &[drive]))],
+            _ => vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[0])]
+        }
+    }
+}
+
+#[test]
+fn drive_enable_writes_sequence() {
+    let mut interface = interface();
+    interface.driver.set_responder(drive_responder(true));
+
+    interface.drive_enable(DRIVE_NODE_ID).unwrap();
+
+    let requests: Vec<(u16, Vec<u8>)> = common::transfers(&interface.driver.transmitted)
+        .iter()
+        .map(|transfer| (transfer.props.port_id, transfer.payload[..transfer.payload_size].to_vec()))
+        .collect();
+    let keys: Vec<String> = requests.iter()
+        .filter(|(port_id, _)| *port_id == 0x81)
+        .map(|(_, payload)| Dict::deserialize(payload).unwrap().key)
+        .collect();
+    assert_eq!(keys, ["cmdval", "cmdarray", "drive"]);
+    // Read back to confirm the transition.
+    assert_eq!(requests.last().unwrap().0, 0x82);
+    assert_eq!(interface.drive_state(DRIVE_NODE_ID), DriveState::Enabled);
+}
+
+#[test]
+fn drive_enable_reports_fault() {
+    let mut interface = interface();
+    let mut responder = drive_responder(true);
+    interface.driver.set_responder(move |frame| {
+        let mut ret = responder(frame);
+        if decode(frame).0 == 0x82 {
+            ret.push(message(DRIVE_NODE_ID, 0x17C0, &[0x01]));
+        }
+        ret
+    });
+
+    let err = interface.drive_enable(DRIVE_NODE_ID).unwrap_err();
+    assert!(matches!(err, Error::DriveFault { channel: DRIVE_NODE_ID, code: ErrorCode::Other(0x01) }));
+    assert_eq!(interface.drive_state(DRIVE_NODE_ID), DriveState::Faulted);
+    assert!(matches!(interface.drive_enable(DRIVE_NODE_ID), Err(Error::InvalidDriveState { state: DriveState::Faulted, .. })));
+}
+
+#[test]
+fn drive_enable_detects_drive_staying_off() {
+    let mut interface = interface();
+    interface.driver.set_responder(drive_responder(false));
+
+    assert!(matches!(interface.drive_enable(DRIVE_NODE_ID), Err(Error::InvalidDriveState { .. })));
+    assert_eq!(interface.drive_state(DRIVE_NODE_ID), DriveState::Disabled);
+}
+
+#[tokio::test]
+async fn async_set_value_succeeds() {
+    let mut interface = interface();
+    interface.driver.set_responder(|frame| {
+        let (_, _, transfer_id, _) = decode(frame);
+        vec![response(DRIVE_NODE_ID, RESULT_PORT_ID, transfer_id, &[0])]
+    });
+
+    interface.async_send_digitalservo_set_value(DRIVE_NODE_ID, "cmdval", &[0.0]).await.unwrap();
+}
+
+#[tokio::test]
+async fn async_get_value_times_out() {
+    let mut interface = interface();
+
+    let err = interface.async_send_digitalservo_get_value(DRIVE_NODE_ID, "cmdval").await.unwrap_err();
+
+    assert!(matches!(err, Error::Timeout { attempts: 3, .. }));
+}
+
+#[test]
+fn codes_decode_with_descriptions() {
+    let mut interface = interface();
+    assert_eq!(interface.get_digitalservo_general_status(), GeneralStatus::NoData);
+    assert_eq!(u8::from(GeneralStatus::NoData), 0xFF);
+
+    interface.driver.push_rx(response(DRIVE_NODE_ID, RESULT_PORT_ID, 0, &[1]));
+    interface.load_frames().unwrap();
+    assert_eq!(interface.get_digitalservo_general_status(), GeneralStatus::Result(ResultCode::Other(1)));
+
+    assert_eq!(ErrorCode::from(0x00), ErrorCode::NoError);
+    assert_eq!(ErrorCode::from(0x01).to_string(), "drive error (0x01)");
+    assert_eq!(u8::from(ErrorCode::Other(0x42)), 0x42);
+    let err = Error::DriveResult { channel: DRIVE_NODE_ID, key: "cmdval".into(), code: ResultCode::Other(2) };
+    assert_eq!(err.to_string(), "node 3 rejected \"cmdval\": rejected by the drive (0x02)");
+}
@@ -0,0 +1,90 @@
+#![cfg(feature="drvcan_v2")]
+
+use std::time::Duration;
+
+use cands_cyphal::backend::{SimBackend, SimulatedDrive};
+use cands_cyphal::digitalservo::v2::{lookup, Access, CmdArray, CmdVal, Drive, Parameter, ParameterType, Readable, Writable, CATALOG, CMDARRAY_LEN};
+use cands_cyphal::serde::digitalservo::dictionary::DigitalServoPrimitiveData;
+use cands_cyphal::{CANInterface, CANRuntime, Error};
+
+const AXIS: u8 = 1;
+
+/// A drive-specific parameter with a range.
+struct CurrentLimit;
+
+impl Parameter for CurrentLimit {
+    type Value = f32;
+    const NAME: &'static str = "ilimit";
+    const UNIT: &'static str = "A";
+    const ACCESS: Access = Access::ReadWrite;
+    const RANGE: Option<(f64, f64)> = Some((0.0, 10.0));
+}
+impl Readable for CurrentLimit {}
+impl Writable for CurrentLimit {}
+
+fn interface() -> CANInterface<SimBackend> {
+    let drive = SimulatedDrive::new(AXIS).with_value("ilimit", &[1.0f32]);
+    let mut interface = CANInterface::with_backend(SimBackend::new().with_drive(drive)).unwrap();
+    interface.set_timeout(Duration::from_millis(20));
+    interface
+}
+
+#[test]
+fn catalog_describes_known_parameters() {
+    assert_eq!(CATALOG.len(), 3);
+    let cmdarray = lookup("cmdarray").unwrap();
+    assert_eq!((cmdarray.data_type, cmdarray.arity, cmdarray.access), (ParameterType::F64, 16, Access::ReadWrite));
+    assert_eq!(lookup("drive").unwrap().data_type, ParameterType::Bool);
+    assert!(lookup("nokey").is_none());
+
+    assert_eq!(CurrentLimit::INFO.unit, "A");
+    assert_eq!(CurrentLimit::INFO.data_type, ParameterType::F32);
+}
+
+#[test]
+fn typed_set_and_get() {
+    let mut interface = interface();
+
+    interface.set::<Drive>(AXIS, true).unwrap();
+    assert!(matches!(interface.set::<CmdArray>(AXIS, [0.0; CMDARRAY_LEN]), Err(Error::InvalidDriveState { .. })));
+    interface.drive_enable(AXIS).unwrap();
+    let cmdarray: [f64; CMDARRAY_LEN] = std::array::from_fn(|i| i as f64);
+    interface.set::<CmdArray>(AXIS, cmdarray).unwrap();
+    interface.set::<CurrentLimit>(AXIS, 2.5).unwrap();
+
+    assert_eq!(interface.driver.drive(AXIS).unwrap().get("drive"), Some(&[DigitalServoPrimitiveData::Bool(true)][..]));
+    assert!(interface.get::<Drive>(AXIS).unwrap());
+    assert_eq!(interface.get::<CmdArray>(AXIS).unwrap(), cmdarray);
+    assert_eq!(interface.get::<CurrentLimit>(AXIS).unwrap(), 2.5);
+}
+
+#[test]
+fn out_of_range_value_is_not_transmitted() {
+    let mut interface = interface();
+
+    let err = interface.set::<CurrentLimit>(AXIS, 12.0).unwrap_err();
+    assert!(matches!(err, Error::InvalidValue { ref key, .. } if key == "ilimit"));
+    assert_eq!(interface.driver.transmitted, 0);
+    assert!(CmdArray::INFO.validate(&[0.0; 4]).is_err());
+    assert!(CmdVal::INFO.validate(&[f64::NAN]).is_err());
+    assert!(CmdVal::INFO.validate(&[f64::INFINITY]).is_err());
+    assert!(CmdVal::INFO.validate(&[-1e9]).is_ok());
+}
+
+#[test]
+fn type_mismatch_is_reported() {
+    let mut interface = interface();
+    interface.driver.drive_mut(AXIS).unwrap().values.insert("cmdarray".into(), vec![0.0.into(); 3]);
+
+    assert!(matches!(interface.get::<CmdArray>(AXIS), Err(Error::TypeConversion { .. })));
+}
+
+#[tokio::test]
+async fn typed_runtime_requests() {
+    let runtime = CANRuntime::spawn(interface());
+
+    runtime.drive_enable(AXIS).await.unwrap();
+    runtime.set::<CmdVal>(AXIS, 7.5).await.unwrap();
+    assert_eq!(runtime.get::<CmdVal>(AXIS).await.unwrap(), 7.5);
+    assert!(runtime.set::<CurrentLimit>(AXIS, -1.0).await.is_err());
+}
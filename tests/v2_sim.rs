@@ -0,0 +1,386 @@
+#![cfg(feature="drvcan_v2")]
+
+use std::time::Duration;
+
+use cands_cyphal::backend::{SimBackend, SimulatedDrive, SIM_ERROR_HISTORY_KEY, SIM_FAULT_RESET_KEY, SIM_RESULT_LENGTH_MISMATCH, SIM_RESULT_UNKNOWN_KEY};
+use cands_cyphal::digitalservo::v2::{CmdVal, FaultKeys};
+use cands_cyphal::digitalservo::{DriveState, ErrorCode, ResultCode};
+use cands_cyphal::serde::digitalservo::dictionary::DigitalServoPrimitiveData;
+use cands_cyphal::{CANInterface, CANRuntime, Error};
+
+const AXIS_1: u8 = 1;
+const AXIS_2: u8 = 2;
+
+fn interface(backend: SimBackend) -> CANInterface<SimBackend> {
+    let mut interface = CANInterface::with_backend(backend).unwrap();
+    interface.set_timeout(Duration::from_millis(20));
+    interface.set_retry_count(5);
+    interface.set_fault_keys(FaultKeys::new().with_reset(SIM_FAULT_RESET_KEY).with_history(SIM_ERROR_HISTORY_KEY));
+    interface.set_fault_settle_time(Duration::from_millis(5));
+    interface
+}
+
+fn two_axes() -> SimBackend {
+    SimBackend::new()
+        .with_drive(SimulatedDrive::new(AXIS_1))
+        .with_drive(SimulatedDrive::new(AXIS_2))
+}
+
+#[test]
+fn drive_enable_and_disable() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable(AXIS_1).unwrap();
+    assert_eq!(interface.driver.drive(AXIS_1).unwrap().get("drive"), Some(&[DigitalServoPrimitiveData::Bool(true)][..]));
+    assert_eq!(interface.driver.drive(AXIS_2).unwrap().get("drive"), Some(&[DigitalServoPrimitiveData::Bool(false)][..]));
+
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Enabled);
+    assert_eq!(interface.drive_state(AXIS_2), DriveState::Disabled);
+
+    interface.drive_disable(AXIS_1).unwrap();
+    assert_eq!(interface.driver.drive(AXIS_1).unwrap().get("drive"), Some(&[DigitalServoPrimitiveData::Bool(false)][..]));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+}
+
+#[test]
+fn motion_requires_enabled_axis() {
+    let mut interface = interface(two_axes());
+
+    let err = interface.set::<CmdVal>(AXIS_1, 1.0).unwrap_err();
+    assert!(matches!(err, Error::InvalidDriveState { channel: AXIS_1, state: DriveState::Disabled, .. }));
+    assert_eq!(interface.driver.transmitted, 0);
+
+    interface.drive_enable(AXIS_1).unwrap();
+    interface.driver.raise_error(AXIS_1, 0x01);
+    assert!(matches!(interface.set::<CmdVal>(AXIS_1, 1.0), Err(Error::InvalidDriveState { state: DriveState::Faulted, .. })));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Faulted);
+    assert!(interface.drive_enable(AXIS_1).is_err());
+    // The error stays available to the caller.
+    assert_eq!(interface.get_error(Some(AXIS_1)).unwrap().unwrap()[0].data, ErrorCode::Other(0x01));
+
+    interface.drive_disable(AXIS_1).unwrap();
+    interface.drive_enable(AXIS_1).unwrap();
+    interface.set::<CmdVal>(AXIS_1, 1.0).unwrap();
+}
+
+#[test]
+fn legacy_shorthands_ignore_drive_state() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable_all().unwrap();
+    interface.send_cmdval(AXIS_1, 1.0).unwrap();
+    interface.send_cmdarray(AXIS_2, &[2.0; 16]).unwrap();
+
+    assert_eq!(interface.driver.drive(AXIS_1).unwrap().get("cmdval"), Some(&[DigitalServoPrimitiveData::F64(1.0)][..]));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+}
+
+#[test]
+fn failed_enable_rolls_back() {
+    let mut interface = interface(two_axes());
+
+    // No drive answers on node 3.
+    assert!(matches!(interface.drive_enable(3), Err(Error::Timeout { channel: 3, .. })));
+    assert_eq!(interface.drive_state(3), DriveState::Disabled);
+
+    interface.driver.drive_mut(AXIS_1).unwrap().forced_result = Some(SIM_RESULT_UNKNOWN_KEY);
+    assert!(matches!(interface.drive_enable(AXIS_1), Err(Error::DriveResult { .. })));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+}
+
+#[test]
+fn failed_disable_rolls_back() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable(AXIS_1).unwrap();
+    interface.driver.drive_mut(AXIS_1).unwrap().forced_result = Some(SIM_RESULT_UNKNOWN_KEY);
+    assert!(matches!(interface.drive_disable(AXIS_1), Err(Error::DriveResult { .. })));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Enabled);
+
+    interface.driver.raise_error(AXIS_1, 0x02);
+    assert!(interface.drive_disable(AXIS_1).is_err());
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Faulted);
+
+    interface.driver.drive_mut(AXIS_1).unwrap().forced_result = None;
+    interface.drive_disable(AXIS_1).unwrap();
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+}
+
+#[tokio::test]
+async fn runtime_drive_transitions() {
+    let runtime = CANRuntime::spawn(interface(two_axes()));
+
+    assert!(runtime.set::<CmdVal>(AXIS_2, 1.0).await.is_err());
+    runtime.drive_enable(AXIS_2).await.unwrap();
+    assert_eq!(runtime.drive_state(AXIS_2), DriveState::Enabled);
+    runtime.set::<CmdVal>(AXIS_2, 1.0).await.unwrap();
+
+    runtime.drive_disable(AXIS_2).await.unwrap();
+    assert_eq!(runtime.drive_state(AXIS_2), DriveState::Disabled);
+    assert_eq!(runtime.interface().driver.drive(AXIS_2).unwrap().get("cmdval"), Some(&[DigitalServoPrimitiveData::F64(0.0)][..]));
+
+    assert!(runtime.drive_enable(3).await.is_err());
+    assert_eq!(runtime.drive_state(3), DriveState::Disabled);
+
+    runtime.drive_enable(AXIS_2).await.unwrap();
+    runtime.interface().driver.drive_mut(AXIS_2).unwrap().forced_result = Some(SIM_RESULT_UNKNOWN_KEY);
+    assert!(matches!(runtime.drive_disable(AXIS_2).await, Err(Error::DriveResult { .. })));
+    assert_eq!(runtime.drive_state(AXIS_2), DriveState::Enabled);
+    runtime.interface().driver.drive_mut(AXIS_2).unwrap().forced_result = None;
+    runtime.drive_disable(AXIS_2).await.unwrap();
+
+    // Like `CANInterface::set`, a reported error blocks motion.
+    runtime.drive_enable(AXIS_2).await.unwrap();
+    runtime.interface().driver.raise_error(AXIS_2, 0x01);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(matches!(runtime.set::<CmdVal>(AXIS_2, 1.0).await, Err(Error::InvalidDriveState { state: DriveState::Faulted, .. })));
+}
+
+#[test]
+fn clear_fault_recovers_axis() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable(AXIS_1).unwrap();
+    assert!(matches!(interface.clear_fault(AXIS_1), Err(Error::InvalidDriveState { state: DriveState::Enabled, .. })));
+
+    interface.driver.raise_error(AXIS_1, 0x04);
+    assert!(interface.set::<CmdVal>(AXIS_1, 1.0).is_err());
+    interface.driver.drive_mut(AXIS_1).unwrap().fault_persists = true;
+    assert!(matches!(interface.clear_fault(AXIS_1), Err(Error::DriveFault { code: ErrorCode::Other(0x04), .. })));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Faulted);
+
+    interface.driver.drive_mut(AXIS_1).unwrap().fault_persists = false;
+    interface.clear_fault(AXIS_1).unwrap();
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+    assert_eq!(interface.driver.drive(AXIS_1).unwrap().fault, None);
+    interface.drive_enable(AXIS_1).unwrap();
+    interface.set::<CmdVal>(AXIS_1, 1.0).unwrap();
+}
+
+#[test]
+fn fault_repeated_within_settle_time_keeps_axis_faulted() {
+    let mut interface = interface(two_axes());
+    interface.set_fault_settle_time(Duration::from_millis(100));
+
+    interface.driver.raise_error(AXIS_1, 0x04);
+    let drive = interface.driver.drive_mut(AXIS_1).unwrap();
+    drive.fault_persists = true;
+    drive.fault_repeat_delay = Duration::from_millis(30);
+
+    assert!(matches!(interface.clear_fault(AXIS_1), Err(Error::DriveFault { code: ErrorCode::Other(0x04), .. })));
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Faulted);
+
+    // A fault reported after the settle time is only caught by the tracking of later calls.
+    interface.driver.drive_mut(AXIS_1).unwrap().fault_repeat_delay = Duration::from_millis(200);
+    interface.clear_fault(AXIS_1).unwrap();
+    assert_eq!(interface.drive_state(AXIS_1), DriveState::Disabled);
+    std::thread::sleep(Duration::from_millis(250));
+    assert!(matches!(interface.set::<CmdVal>(AXIS_1, 1.0), Err(Error::InvalidDriveState { state: DriveState::Faulted, .. })));
+}
+
+#[tokio::test]
+async fn runtime_fault_repeated_within_settle_time() {
+    let mut interface = interface(two_axes());
+    interface.set_fault_settle_time(Duration::from_millis(100));
+    interface.driver.raise_error(AXIS_2, 0x04);
+    let drive = interface.driver.drive_mut(AXIS_2).unwrap();
+    drive.fault_persists = true;
+    drive.fault_repeat_delay = Duration::from_millis(30);
+    let runtime = CANRuntime::spawn(interface);
+
+    assert!(matches!(runtime.clear_fault(AXIS_2).await, Err(Error::DriveFault { code: ErrorCode::Other(0x04), .. })));
+    assert_eq!(runtime.drive_state(AXIS_2), DriveState::Faulted);
+}
+
+#[test]
+fn fault_keys_must_be_configured() {
+    let mut interface = interface(two_axes());
+    interface.set_fault_keys(FaultKeys::new());
+
+    assert!(matches!(interface.clear_fault(AXIS_1), Err(Error::InvalidConfig(_))));
+    assert!(matches!(interface.read_error_history(AXIS_1), Err(Error::InvalidConfig(_))));
+    assert_eq!(interface.driver.transmitted, 0);
+
+    // Other firmware, other keys.
+    interface.driver.drive_mut(AXIS_1).unwrap().values.insert("fault_log".into(), vec![0x21u8.into(), 0u8.into()]);
+    interface.set_fault_keys(FaultKeys::new().with_history("fault_log"));
+    assert_eq!(interface.read_error_history(AXIS_1).unwrap(), vec![ErrorCode::Other(0x21)]);
+}
+
+#[test]
+fn error_history_and_events() {
+    let mut interface = interface(two_axes());
+
+    assert_eq!(interface.read_error_history(AXIS_1).unwrap(), vec![]);
+    interface.driver.raise_error(AXIS_1, 0x01);
+    interface.driver.raise_error(AXIS_2, 0x05);
+    interface.driver.raise_error(AXIS_1, 0x99);
+
+    let events = interface.get_error_events(Some(AXIS_1)).unwrap();
+    assert_eq!(events.iter().map(|event| (event.node_id, event.code)).collect::<Vec<_>>(), vec![(AXIS_1, ErrorCode::Other(0x01)), (AXIS_1, ErrorCode::Other(0x99))]);
+    assert_eq!(interface.read_error_history(AXIS_1).unwrap(), vec![ErrorCode::Other(0x01), ErrorCode::Other(0x99)]);
+    assert_eq!(interface.read_error_history(AXIS_2).unwrap(), vec![ErrorCode::Other(0x05)]);
+}
+
+#[tokio::test]
+async fn runtime_error_stream_and_fault_reset() {
+    let runtime = CANRuntime::spawn(interface(two_axes()));
+    let mut errors = runtime.subscribe_errors(Some(AXIS_2));
+
+    runtime.interface().driver.raise_error(AXIS_1, 0x01);
+    runtime.interface().driver.raise_error(AXIS_2, 0x06);
+    let event = tokio::time::timeout(Duration::from_millis(500), errors.recv()).await.unwrap().unwrap();
+    assert_eq!((event.node_id, event.code), (AXIS_2, ErrorCode::Other(0x06)));
+    assert_eq!(runtime.read_error_history(AXIS_2).await.unwrap(), vec![ErrorCode::Other(0x06)]);
+
+    runtime.clear_fault(AXIS_2).await.unwrap();
+    assert_eq!(runtime.interface().driver.drive(AXIS_2).unwrap().fault, None);
+    assert_eq!(runtime.drive_state(AXIS_2), DriveState::Disabled);
+}
+
+#[test]
+fn drive_enable_all_broadcasts() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable_all().unwrap();
+
+    for node_id in [AXIS_1, AXIS_2] {
+        assert_eq!(interface.driver.drive(node_id).unwrap().get("drive"), Some(&[DigitalServoPrimitiveData::Bool(true)][..]));
+    }
+}
+
+#[test]
+fn set_and_get_scalar() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable(AXIS_2).unwrap();
+    interface.send_cmdval(AXIS_2, 12.5).unwrap();
+
+    assert_eq!(interface.get_scalar_response::<f64>(AXIS_2, "cmdval").unwrap(), Some(12.5));
+    assert_eq!(interface.get_scalar_response::<f64>(AXIS_1, "cmdval").unwrap(), Some(0.0));
+}
+
+#[test]
+fn set_and_get_array() {
+    let mut interface = interface(two_axes());
+
+    interface.drive_enable(AXIS_1).unwrap();
+    let cmdarray: [f64; 16] = std::array::from_fn(|i| i as f64);
+    interface.send_cmdarray(AXIS_1, &cmdarray).unwrap();
+
+    let value = interface.get_vector_response(AXIS_1, "cmdarray").unwrap();
+    assert_eq!(value, cmdarray.map(DigitalServoPrimitiveData::F64));
+
+    let err = interface.send_cmdarray(AXIS_1, &[1.0, 2.0, 3.0, 4.0]).unwrap_err();
+    assert!(matches!(err, Error::DriveResult { code: ResultCode::Other(SIM_RESULT_LENGTH_MISMATCH), .. }));
+    assert_eq!(interface.get_vector_response(AXIS_1, "cmdarray").unwrap(), value);
+}
+
+#[test]
+fn set_value_with_crc_split_over_last_frames() {
+    // Some of these lengths leave only part of the transfer CRC in the last frame.
+    for len in 1..=160 {
+        let backend = SimBackend::new().with_drive(SimulatedDrive::new(AXIS_1).with_value("trace", &vec![0u8; len]));
+        let mut interface = interface(backend);
+        let value: Vec<u8> = (0..len).map(|x| x as u8).collect();
+
+        interface.send_digitalservo_set_value(AXIS_1, "trace", &value).unwrap();
+        assert_eq!(interface.driver.drive(AXIS_1).unwrap().get("trace").unwrap().len(), len);
+    }
+}
+
+#[test]
+fn get_long_value_spans_multiple_frames() {
+    let value: Vec<f64> = (0..32).map(|x| x as f64).collect();
+    let backend = SimBackend::new().with_drive(SimulatedDrive::new(AXIS_1).with_value("trace", &value));
+    let mut interface = interface(backend);
+
+    let ret = interface.get_vector_response(AXIS_1, "trace").unwrap();
+    assert_eq!(ret.len(), 32);
+    assert_eq!(ret[31], DigitalServoPrimitiveData::F64(31.0));
+}
+
+#[test]
+fn unknown_key_is_rejected() {
+    let mut interface = interface(two_axes());
+    interface.set_retry_count(2);
+
+    let err = interface.send_digitalservo_set_value(AXIS_1, "nokey", &[1.0]).unwrap_err();
+    assert!(matches!(err, Error::DriveResult { code: ResultCode::Other(SIM_RESULT_UNKNOWN_KEY), .. }));
+    assert_eq!(interface.driver.transmitted, 1);
+}
+
+#[test]
+fn forced_result_is_rejected() {
+    let mut backend = two_axes();
+    backend.drive_mut(AXIS_1).unwrap().forced_result = Some(SIM_RESULT_UNKNOWN_KEY);
+    let mut interface = interface(backend);
+    interface.set_retry_count(2);
+
+    assert!(interface.send_digitalservo_set_value(AXIS_1, "cmdval", &[1.0]).is_err());
+    assert!(interface.send_digitalservo_set_value(AXIS_2, "cmdval", &[1.0]).is_ok());
+}
+
+#[test]
+fn retries_through_packet_loss() {
+    let backend = two_axes().with_packet_loss(0.3, 0.3).with_seed(7);
+    let mut interface = interface(backend);
+    interface.set_retry_count(50);
+
+    for i in 0..10 {
+        interface.send_digitalservo_set_value(AXIS_1, "cmdval", &[i as f64]).unwrap();
+    }
+    assert_eq!(interface.driver.drive(AXIS_1).unwrap().get("cmdval"), Some(&[DigitalServoPrimitiveData::F64(9.0)][..]));
+    assert!(interface.driver.transmitted > 10);
+}
+
+#[test]
+fn total_loss_times_out() {
+    let backend = two_axes().with_packet_loss(1.0, 0.0);
+    let mut interface = interface(backend);
+    interface.set_retry_count(3);
+
+    let err = interface.send_digitalservo_set_value(AXIS_1, "cmdval", &[1.0]).unwrap_err();
+    assert!(matches!(err, Error::Timeout { channel: AXIS_1, attempts: 3, .. }));
+    assert_eq!(interface.driver.transmitted, 3);
+}
+
+#[test]
+fn latency_beyond_timeout_fails() {
+    let backend = two_axes().with_latency(Duration::from_millis(50));
+    let mut interface = interface(backend);
+    interface.set_retry_count(2);
+
+    assert!(matches!(interface.send_digitalservo_set_value(AXIS_1, "cmdval", &[1.0]), Err(Error::Timeout { .. })));
+}
+
+#[test]
+fn latency_within_timeout_succeeds() {
+    let backend = two_axes().with_latency(Duration::from_millis(5));
+    let mut interface = interface(backend);
+
+    interface.send_digitalservo_set_value(AXIS_1, "cmdval", &[1.0]).unwrap();
+    assert_eq!(interface.driver.transmitted, 1);
+}
+
+#[test]
+fn errors_are_published() {
+    let mut interface = interface(two_axes());
+
+    interface.driver.raise_error(AXIS_2, 0x21);
+
+    let errors = interface.get_error(Some(AXIS_2)).unwrap().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].data, ErrorCode::Other(0x21));
+    assert_eq!(errors[0].props.port_id, 0x17C0);
+}
+
+#[tokio::test]
+async fn async_requests_against_simulated_drive() {
+    let mut interface = interface(two_axes());
+
+    interface.async_send_digitalservo_set_value(AXIS_1, "cmdval", &[3.0]).await.unwrap();
+    let ret = interface.async_send_digitalservo_get_value(AXIS_1, "cmdval").await.unwrap();
+
+    assert_eq!(ret[0].data.value, [DigitalServoPrimitiveData::F64(3.0)]);
+}